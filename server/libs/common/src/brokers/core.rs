@@ -1,5 +1,13 @@
 use async_trait::async_trait;
 
+/// A message handed back by [`BrokerCore::consume`], along with the
+/// delivery id needed to [`BrokerCore::ack`] or [`BrokerCore::nack`] it.
+#[derive(Debug, Clone)]
+pub struct Delivery {
+    pub id: String,
+    pub payload: Vec<u8>,
+}
+
 #[async_trait]
 pub trait BrokerCore: Send + Sync {
     async fn register_exchange(
@@ -26,4 +34,23 @@ pub trait BrokerCore: Send + Sync {
         message_id: &str,
         task_id: &str,
     ) -> Result<(), Box<dyn std::error::Error>>;
+
+    /// Blocks for the next message on `queue` and returns it with a delivery
+    /// id. Brokers without a recoverable-delivery model (e.g. the test mock
+    /// broker) can leave this unimplemented.
+    async fn consume(&self, _queue: &str) -> Result<Delivery, Box<dyn std::error::Error>> {
+        Err("consume is not supported by this broker".into())
+    }
+
+    /// Acknowledges a delivery, removing it from the broker's redelivery set.
+    async fn ack(&self, _queue: &str, _delivery_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    /// Declines a delivery without acknowledging it. What happens to it next
+    /// is up to the broker: `PgBroker` makes it claimable again,
+    /// `RabbitBroker` dead-letters it, and `RedisBroker` leaves it pending.
+    async fn nack(&self, _queue: &str, _delivery_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
 }
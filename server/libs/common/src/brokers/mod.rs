@@ -1,15 +1,46 @@
 pub mod core;
+pub mod postgres;
 pub mod rabbit;
+pub mod redis;
 pub mod testing;
 
 use core::BrokerCore;
+use postgres::PgBroker;
 use rabbit::RabbitBroker;
+use redis::RedisBroker;
+use time::OffsetDateTime;
+use tracing::error;
 use uuid::Uuid;
 
+use std::str::FromStr;
 use std::sync::Arc;
 
 use crate::{TaskInstance, Worker};
 
+/// How a task handed to [`Broker::publish_scheduled`] should be released to
+/// the queue: once at a fixed time, or repeatedly on a cron pattern.
+#[derive(Debug, Clone)]
+pub enum Scheduled {
+    ScheduleOnce(OffsetDateTime),
+    CronPattern(String),
+}
+
+/// Computes the next fire time for a cron expression after `after`. Falls
+/// back to `after` itself if the expression fails to parse, so a bad pattern
+/// degrades to "fire immediately" rather than never firing at all.
+fn next_cron_fire_time(expression: &str, after: OffsetDateTime) -> OffsetDateTime {
+    if let Ok(schedule) = cron::Schedule::from_str(expression) {
+        let after_chrono = chrono::DateTime::from_timestamp(after.unix_timestamp(), 0)
+            .unwrap_or_else(chrono::Utc::now);
+        if let Some(next) = schedule.after(&after_chrono).next() {
+            if let Ok(next) = OffsetDateTime::from_unix_timestamp(next.timestamp()) {
+                return next;
+            }
+        }
+    }
+    after
+}
+
 async fn create_broker_connection(
     uri: &str,
 ) -> Result<Arc<dyn BrokerCore>, Box<dyn std::error::Error>> {
@@ -17,6 +48,8 @@ async fn create_broker_connection(
 
     match prefix {
         "amqp" => Ok(Arc::new(RabbitBroker::new(uri).await?)),
+        "postgres" | "postgresql" => Ok(Arc::new(PgBroker::new(uri).await?)),
+        "redis" => Ok(Arc::new(RedisBroker::new(uri).await?)),
         _ => Err("Invalid broker URI".into()),
     }
 }
@@ -49,27 +82,43 @@ impl Broker {
         })
     }
 
+    /// The shared topic-exchange queue name for a task kind. Every worker
+    /// registered for `task_kind_name` binds to this same queue, so they
+    /// compete for deliveries instead of each getting a dedicated queue.
+    fn task_kind_queue(task_kind_name: &str) -> String {
+        format!("task_kind.{}", task_kind_name)
+    }
+
     pub async fn register_worker(
         &mut self,
         worker: Worker,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Create a unique queue for this worker using its ID
-        let worker_queue = worker.id.to_string();
-
-        self.broker.register_queue(Self::SUBMISSION_EXCHANGE, &worker_queue, &worker_queue).await?;
+        // Bind a shared queue per task kind the worker can handle, routed by
+        // the kind's name, rather than a queue unique to this worker. Any
+        // number of identical workers can then bind to the same queue and
+        // compete for deliveries, so horizontal scaling doesn't require a
+        // fresh 1:1 queue per worker.
+        for task_kind in &worker.task_kind {
+            let queue = Self::task_kind_queue(&task_kind.name);
+            self.broker
+                .register_queue(Self::SUBMISSION_EXCHANGE, &queue, &task_kind.name)
+                .await?;
+        }
 
         self.workers.push(worker);
         Ok(())
     }
 
     pub async fn remove_worker(&mut self, worker_id: &Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        // The task-kind queues are shared across workers, so removing one
+        // worker doesn't delete them - other workers bound to the same kind
+        // may still be consuming from them.
         let index: usize = self
             .workers
             .iter()
             .position(|worker| worker.id == *worker_id)
             .unwrap();
         self.workers.remove(index);
-        self.broker.delete_queue(&worker_id.to_string()).await?;
 
         Ok(())
     }
@@ -77,32 +126,97 @@ impl Broker {
     pub async fn publish(
         &mut self,
         task: &TaskInstance,
-    ) -> Result<Uuid, Box<dyn std::error::Error>> {
-        let worker = (0..self.workers.len())
-            // Cycle the workers list in a round robin fashion
-            .map(|_| {
-                let cur_worker = &self.workers[self.workers_index];
-                self.workers_index = (self.workers_index + 1) % self.workers.len();
-                cur_worker
-            })
-            // Find the first worker that can handle the task
-            .find(|cur_worker| cur_worker.can_handle(task))
-            .ok_or("No available worker")?;
-
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // Convert input data to bytes
         let payload = serde_json::to_vec(&task.input_data)?;
 
-        // Use task type as exchange, worker ID as routing key
-        self.broker
+        // Route by task kind instead of picking one worker: any worker bound
+        // to this kind's queue can pick it up, so the task fans out to
+        // whichever identical worker is free rather than being pinned to a
+        // single worker id at creation time.
+        Self::publish_payload(&self.broker, Self::SUBMISSION_EXCHANGE, task, &payload).await
+    }
+
+    /// Releases `task` to its task-kind queue on a schedule instead of
+    /// immediately: once at a fixed time for [`Scheduled::ScheduleOnce`], or
+    /// repeatedly for [`Scheduled::CronPattern`], re-computing the next fire
+    /// time after each dispatch so a recurring definition keeps firing
+    /// without the caller re-submitting it.
+    ///
+    /// Spawns a background task that owns the wait/dispatch loop and holds
+    /// its own clone of the broker handle, so this method returns
+    /// immediately and the schedule keeps running independent of `self`.
+    pub async fn publish_scheduled(
+        &self,
+        task: TaskInstance,
+        schedule: Scheduled,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let payload = serde_json::to_vec(&task.input_data)?;
+        let broker = self.broker.clone();
+        let exchange = self.submission_exchange;
+
+        match schedule {
+            Scheduled::ScheduleOnce(at) => {
+                tokio::spawn(async move {
+                    sleep_until(at).await;
+                    if let Err(err) =
+                        Self::publish_payload(&broker, exchange, &task, &payload).await
+                    {
+                        error!(%err, task_id = %task.id, "Scheduled publish failed");
+                    }
+                });
+            }
+            Scheduled::CronPattern(pattern) => {
+                tokio::spawn(async move {
+                    let mut after = OffsetDateTime::now_utc();
+                    loop {
+                        let next_fire = next_cron_fire_time(&pattern, after);
+                        sleep_until(next_fire).await;
+
+                        if let Err(err) =
+                            Self::publish_payload(&broker, exchange, &task, &payload).await
+                        {
+                            error!(%err, task_id = %task.id, %pattern, "Scheduled publish failed");
+                        }
+
+                        after = next_fire;
+                    }
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Shared send path for both [`Broker::publish`] and
+    /// [`Broker::publish_scheduled`]: routes by task kind on the submission
+    /// exchange so any worker bound to that kind's queue can pick it up.
+    async fn publish_payload(
+        broker: &Arc<dyn BrokerCore>,
+        exchange: &'static str,
+        task: &TaskInstance,
+        payload: &[u8],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        broker
             .publish_message(
-                Self::SUBMISSION_EXCHANGE,
-                &worker.id.to_string(),
-                &payload,
+                exchange,
+                &task.task_kind.name,
+                payload,
+                &task.id.to_string(),
                 &task.id.to_string(),
             )
-            .await?;
+            .await
+    }
+}
 
-        Ok(worker.id)
+/// Sleeps until `at`, returning immediately if it's already in the past.
+async fn sleep_until(at: OffsetDateTime) {
+    let delay = at - OffsetDateTime::now_utc();
+    if delay > time::Duration::ZERO {
+        tokio::time::sleep(std::time::Duration::from_secs_f64(
+            delay.as_seconds_f64(),
+        ))
+        .await;
     }
 }
 
@@ -124,6 +238,13 @@ mod test {
         assert!(broker.is_ok());
     }
 
+    #[tokio::test]
+    async fn test_create_broker_connection_redis() {
+        let uri = "redis://localhost".to_string();
+        let broker = create_broker_connection(&uri).await;
+        assert!(broker.is_ok());
+    }
+
     #[tokio::test]
     async fn test_broker_new() {
         let uri = "amqp://localhost".to_string();
@@ -182,17 +303,15 @@ mod test {
     }
 
     #[tokio::test]
-    async fn test_no_available_worker() {
+    async fn test_publish_routes_by_task_kind_without_registered_worker() {
+        // publish() no longer picks a single worker to assign the task to -
+        // it routes by task_kind name on the topic exchange, so it succeeds
+        // even when no worker has registered for that kind yet. Whether
+        // anyone is actually listening is a broker-side concern now.
         let uri = "amqp://localhost".to_string();
         let mut broker = Broker::new(&uri).await.unwrap();
         broker.broker = Arc::new(MockBrokerCore {});
 
-        let workers = setup_workers(setup_task_kinds());
-
-        for worker in workers.clone() {
-            broker.register_worker(worker).await.unwrap();
-        }
-
         let task = TaskInstance {
             id: Uuid::new_v4(),
             task_kind: TaskKind::new("task3".to_string()),
@@ -204,6 +323,6 @@ mod test {
         };
 
         let result = broker.publish(&task).await;
-        assert!(result.is_err());
+        assert!(result.is_ok());
     }
 }
@@ -0,0 +1,337 @@
+use crate::brokers::core::{BrokerCore, Delivery};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use sqlx::postgres::PgListener;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify};
+use tokio::time::Duration;
+use tracing::error;
+use uuid::Uuid;
+
+/// How often `consume` polls `broker_messages` for a queue even without a
+/// notification, in case one was missed (e.g. it fired before this
+/// consumer had registered its queue and started listening on the
+/// channel).
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// `BrokerCore` backed purely by Postgres `LISTEN`/`NOTIFY`, so a
+/// deployment that already runs Postgres for the task store doesn't need
+/// RabbitMQ or Redis standing up just to move messages between workers.
+///
+/// A single background task owns the `PgListener` connection and, on each
+/// notification, wakes the [`Notify`] for that channel; `consume` waits on
+/// its queue's `Notify` (or `POLL_INTERVAL`, whichever comes first) and
+/// then claims a row with `FOR UPDATE SKIP LOCKED`, so at most one
+/// concurrent consumer gets any given message.
+#[derive(Clone)]
+pub struct PgBroker {
+    pool: Arc<PgPool>,
+    notifies: Arc<DashMap<String, Arc<Notify>>>,
+    listen_tx: mpsc::UnboundedSender<String>,
+}
+
+impl PgBroker {
+    pub async fn new(uri: &str) -> Result<PgBroker, Box<dyn std::error::Error>> {
+        let pool = PgPool::connect(uri).await?;
+        Self::from_pool(pool).await
+    }
+
+    /// Builds a `PgBroker` around an already-connected pool, reusing its
+    /// connection options for the dedicated `PgListener` connection instead
+    /// of reparsing a URI. Used directly by tests, which are handed a pool
+    /// by the `sqlx::test` harness rather than a connection string.
+    pub async fn from_pool(pool: PgPool) -> Result<PgBroker, Box<dyn std::error::Error>> {
+        let notifies: Arc<DashMap<String, Arc<Notify>>> = Arc::new(DashMap::new());
+        let (listen_tx, listen_rx) = mpsc::unbounded_channel();
+
+        Self::spawn_listener(pool.connect_options().as_ref().clone(), notifies.clone(), listen_rx)
+            .await?;
+
+        Ok(PgBroker {
+            pool: Arc::new(pool),
+            notifies,
+            listen_tx,
+        })
+    }
+
+    /// Owns the single `PgListener` connection for this broker: adds a
+    /// `LISTEN` for each queue name sent over `listen_rx` (one per
+    /// `register_queue` call) and, on every notification received, wakes
+    /// the matching channel's `Notify` so any `consume` call blocked on it
+    /// re-checks for work.
+    async fn spawn_listener(
+        connect_options: sqlx::postgres::PgConnectOptions,
+        notifies: Arc<DashMap<String, Arc<Notify>>>,
+        mut listen_rx: mpsc::UnboundedReceiver<String>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut listener = PgListener::connect_with(&connect_options).await?;
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    channel = listen_rx.recv() => {
+                        let Some(channel) = channel else { break };
+                        if let Err(err) = listener.listen(&channel).await {
+                            error!(%err, %channel, "Failed to LISTEN on Postgres channel");
+                        }
+                    }
+                    notification = listener.recv() => {
+                        match notification {
+                            Ok(notification) => {
+                                if let Some(notify) = notifies.get(notification.channel()) {
+                                    notify.notify_waiters();
+                                }
+                            }
+                            Err(err) => error!(%err, "Postgres LISTEN connection failed"),
+                        }
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    fn notify_for(&self, channel: &str) -> Arc<Notify> {
+        self.notifies
+            .entry(channel.to_string())
+            .or_insert_with(|| Arc::new(Notify::new()))
+            .clone()
+    }
+}
+
+#[async_trait]
+impl BrokerCore for PgBroker {
+    async fn register_exchange(&self, _exchange: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // Postgres has no concept of exchanges; routing is purely by NOTIFY
+        // channel name, so there is nothing to declare.
+        Ok(())
+    }
+
+    async fn register_queue(
+        &self,
+        _exchange: &str,
+        queue: &str,
+        _routing_key: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS broker_messages (
+                id TEXT PRIMARY KEY,
+                queue_name TEXT NOT NULL,
+                message_id TEXT NOT NULL,
+                task_id TEXT NOT NULL,
+                payload BYTEA NOT NULL,
+                state TEXT NOT NULL DEFAULT 'new',
+                scheduled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        self.notify_for(queue);
+        self.listen_tx.send(queue.to_string())?;
+
+        Ok(())
+    }
+
+    async fn delete_queue(&self, queue: &str) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("DELETE FROM broker_messages WHERE queue_name = $1")
+            .bind(queue)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_exchange(&self, _exchange: &str) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(())
+    }
+
+    async fn publish_message(
+        &self,
+        _exchange: &str,
+        routing_key: &str,
+        payload: &[u8],
+        message_id: &str,
+        task_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let id = Uuid::new_v4().to_string();
+
+        sqlx::query(
+            "INSERT INTO broker_messages (id, queue_name, message_id, task_id, payload) VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind(&id)
+        .bind(routing_key)
+        .bind(message_id)
+        .bind(task_id)
+        .bind(payload)
+        .execute(&*self.pool)
+        .await?;
+
+        sqlx::query("SELECT pg_notify($1, $2)")
+            .bind(routing_key)
+            .bind(task_id)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn consume(&self, queue: &str) -> Result<Delivery, Box<dyn std::error::Error>> {
+        loop {
+            // SKIP LOCKED only keeps two consumers from claiming the same row
+            // while the lock is held, so the claim and the state flip to
+            // 'in_progress' have to happen inside the same transaction the
+            // lock was taken in - an autocommit SELECT would release the
+            // lock before anything marked the row as taken.
+            let mut txn = self.pool.begin().await?;
+
+            let row = sqlx::query(
+                r#"
+                SELECT id, payload FROM broker_messages
+                WHERE queue_name = $1 AND state = 'new' AND scheduled_at <= NOW()
+                ORDER BY scheduled_at
+                FOR UPDATE SKIP LOCKED
+                LIMIT 1
+                "#,
+            )
+            .bind(queue)
+            .fetch_optional(&mut *txn)
+            .await?;
+
+            if let Some(row) = row {
+                let id: String = row.get("id");
+                let payload: Vec<u8> = row.get("payload");
+
+                sqlx::query("UPDATE broker_messages SET state = 'in_progress' WHERE id = $1")
+                    .bind(&id)
+                    .execute(&mut *txn)
+                    .await?;
+
+                txn.commit().await?;
+
+                return Ok(Delivery { id, payload });
+            }
+
+            drop(txn);
+
+            let notify = self.notify_for(queue);
+            tokio::select! {
+                _ = notify.notified() => {}
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+        }
+    }
+
+    async fn ack(&self, _queue: &str, delivery_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        sqlx::query("DELETE FROM broker_messages WHERE id = $1")
+            .bind(delivery_id)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn nack(
+        &self,
+        _queue: &str,
+        delivery_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Flip back to 'new' rather than leaving it 'in_progress', so the
+        // next SKIP LOCKED consume (here or on another worker) picks it up
+        // again instead of the row silently never being retried.
+        sqlx::query("UPDATE broker_messages SET state = 'new' WHERE id = $1")
+            .bind(delivery_id)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Publishing then consuming claims the row and flips it to
+    /// 'in_progress'; a second consume on the same queue must not see it
+    /// again until it's nacked back to 'new'.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn consume_claims_exactly_once(pool: PgPool) {
+        let broker = PgBroker::from_pool(pool.clone()).await.unwrap();
+
+        broker
+            .register_queue("exchange", "queue.test", "routing.test")
+            .await
+            .unwrap();
+        broker
+            .publish_message("exchange", "queue.test", b"payload", "msg-1", "task-1")
+            .await
+            .unwrap();
+
+        let delivery = broker.consume("queue.test").await.unwrap();
+        assert_eq!(delivery.payload, b"payload");
+
+        let row: (String,) =
+            sqlx::query_as("SELECT state FROM broker_messages WHERE id = $1")
+                .bind(&delivery.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(row.0, "in_progress");
+    }
+
+    /// Nacking a delivery resets it to 'new' so it can be claimed again.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn nack_makes_message_claimable_again(pool: PgPool) {
+        let broker = PgBroker::from_pool(pool.clone()).await.unwrap();
+
+        broker
+            .register_queue("exchange", "queue.test", "routing.test")
+            .await
+            .unwrap();
+        broker
+            .publish_message("exchange", "queue.test", b"payload", "msg-1", "task-1")
+            .await
+            .unwrap();
+
+        let first = broker.consume("queue.test").await.unwrap();
+        broker.nack("queue.test", &first.id).await.unwrap();
+
+        let row: (String,) =
+            sqlx::query_as("SELECT state FROM broker_messages WHERE id = $1")
+                .bind(&first.id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(row.0, "new");
+    }
+
+    /// Acking a delivery removes it from the table entirely.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn ack_removes_message(pool: PgPool) {
+        let broker = PgBroker::from_pool(pool.clone()).await.unwrap();
+
+        broker
+            .register_queue("exchange", "queue.test", "routing.test")
+            .await
+            .unwrap();
+        broker
+            .publish_message("exchange", "queue.test", b"payload", "msg-1", "task-1")
+            .await
+            .unwrap();
+
+        let delivery = broker.consume("queue.test").await.unwrap();
+        broker.ack("queue.test", &delivery.id).await.unwrap();
+
+        let remaining: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM broker_messages")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
+}
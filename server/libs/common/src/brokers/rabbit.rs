@@ -1,13 +1,47 @@
-use crate::brokers::core::BrokerCore;
+use crate::brokers::core::{BrokerCore, Delivery};
 use async_trait::async_trait;
+use dashmap::DashMap;
+use futures_util::StreamExt;
 use lapin::{
-    options::*, types::{AMQPValue, FieldTable}, BasicProperties, Connection, ConnectionProperties, ExchangeKind,
+    acker::Acker,
+    options::*,
+    types::{AMQPValue, FieldTable},
+    BasicProperties, Connection, ConnectionProperties, Consumer, ExchangeKind,
 };
 use std::sync::Arc;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+/// How long a message may sit in its work queue before it expires and
+/// routes to the queue's dead-letter exchange, rather than being
+/// redelivered forever.
+const MESSAGE_TTL_MS: i64 = 24 * 60 * 60 * 1000;
+/// How many unacked deliveries a single consumer channel may hold at once.
+const PREFETCH_COUNT: u16 = 1;
+
+/// A delivery handed back by `consume` but not yet acked/nacked.
+struct PendingDelivery {
+    acker: Acker,
+}
+
+fn dead_letter_exchange(exchange: &str) -> String {
+    format!("{exchange}.dlx")
+}
+
+fn dead_letter_queue(queue: &str) -> String {
+    format!("{queue}.dlq")
+}
 
 #[derive(Clone)]
 pub struct RabbitBroker {
     connection: Arc<Connection>,
+    /// One consumer per queue `consume` has been called on, so repeated
+    /// calls keep pulling from the same subscription instead of opening a
+    /// fresh one (and competing with it) each time.
+    consumers: Arc<DashMap<String, Arc<Mutex<Consumer>>>>,
+    /// Deliveries handed out by `consume` but not yet resolved by `ack`/
+    /// `nack`, keyed by the id returned alongside them.
+    pending: Arc<DashMap<String, PendingDelivery>>,
 }
 
 impl RabbitBroker {
@@ -16,8 +50,46 @@ impl RabbitBroker {
 
         Ok(RabbitBroker {
             connection: Arc::new(connection),
+            consumers: Arc::new(DashMap::new()),
+            pending: Arc::new(DashMap::new()),
         })
     }
+
+    /// Returns the consumer subscribed to `queue`, opening one (with a
+    /// dedicated channel and a prefetch limit so one consumer can't hoard
+    /// more unacked work than it can get through) the first time it's
+    /// asked for.
+    async fn consumer_for(
+        &self,
+        queue: &str,
+    ) -> Result<Arc<Mutex<Consumer>>, Box<dyn std::error::Error>> {
+        if let Some(consumer) = self.consumers.get(queue) {
+            return Ok(consumer.clone());
+        }
+
+        let channel = self.connection.create_channel().await?;
+        channel
+            .basic_qos(PREFETCH_COUNT, BasicQosOptions::default())
+            .await?;
+
+        let consumer = channel
+            .basic_consume(
+                queue,
+                &format!("fasttq-{}", Uuid::new_v4()),
+                BasicConsumeOptions {
+                    manual_ack: true,
+                    ..Default::default()
+                },
+                FieldTable::default(),
+            )
+            .await?;
+
+        let consumer = Arc::new(Mutex::new(consumer));
+        self.consumers
+            .insert(queue.to_string(), consumer.clone());
+
+        Ok(consumer)
+    }
 }
 
 #[async_trait]
@@ -31,7 +103,7 @@ impl BrokerCore for RabbitBroker {
         channel
             .exchange_declare(
                 exchange,
-                ExchangeKind::Direct,
+                ExchangeKind::Topic,
                 ExchangeDeclareOptions::default(),
                 FieldTable::default(),
             )
@@ -48,14 +120,37 @@ impl BrokerCore for RabbitBroker {
     ) -> Result<(), Box<dyn std::error::Error>> {
         let channel = self.connection.create_channel().await?;
 
+        // Poison messages (rejected without requeue, or expired after
+        // sitting unconsumed past MESSAGE_TTL_MS) route here instead of
+        // being lost or redelivered forever.
+        let dlx = dead_letter_exchange(exchange);
+        let dlq = dead_letter_queue(queue);
+
         channel
-            .queue_declare(
-                queue,
-                QueueDeclareOptions::default(),
+            .exchange_declare(
+                &dlx,
+                ExchangeKind::Fanout,
+                ExchangeDeclareOptions::default(),
                 FieldTable::default(),
             )
             .await?;
 
+        channel
+            .queue_declare(&dlq, QueueDeclareOptions::default(), FieldTable::default())
+            .await?;
+
+        channel
+            .queue_bind(&dlq, &dlx, "", QueueBindOptions::default(), FieldTable::default())
+            .await?;
+
+        let mut args = FieldTable::default();
+        args.insert("x-dead-letter-exchange".into(), AMQPValue::LongString(dlx.into()));
+        args.insert("x-message-ttl".into(), AMQPValue::LongLongInt(MESSAGE_TTL_MS));
+
+        channel
+            .queue_declare(queue, QueueDeclareOptions::default(), args)
+            .await?;
+
         channel
             .queue_bind(queue, exchange, routing_key, QueueBindOptions::default(), FieldTable::default())
             .await?;
@@ -78,7 +173,7 @@ impl BrokerCore for RabbitBroker {
 
         Ok(())
     }
-    
+
     async fn publish_message(
         &self,
         exchange: &str,
@@ -88,10 +183,13 @@ impl BrokerCore for RabbitBroker {
         task_id: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let channel = self.connection.create_channel().await?;
-        
-        // Initialize headers
+
+        // task_id and task_kind are carried as distinct headers - routing_key
+        // is the task_kind name (see Broker::publish), task_id is the task's
+        // own id, and neither should be derived from the other.
         let mut headers = FieldTable::default();
-        headers.insert("task_kind".into(), AMQPValue::LongString(task_id.into()));
+        headers.insert("task_id".into(), AMQPValue::LongString(task_id.into()));
+        headers.insert("task_kind".into(), AMQPValue::LongString(routing_key.into()));
 
         channel
             .basic_publish(
@@ -105,4 +203,50 @@ impl BrokerCore for RabbitBroker {
 
         Ok(())
     }
+
+    async fn consume(&self, queue: &str) -> Result<Delivery, Box<dyn std::error::Error>> {
+        let consumer = self.consumer_for(queue).await?;
+        let mut consumer = consumer.lock().await;
+
+        let delivery = consumer
+            .next()
+            .await
+            .ok_or("consumer stream closed")??;
+
+        let id = Uuid::new_v4().to_string();
+        let payload = delivery.data.clone();
+
+        self.pending.insert(
+            id.clone(),
+            PendingDelivery {
+                acker: delivery.acker,
+            },
+        );
+
+        Ok(Delivery { id, payload })
+    }
+
+    async fn ack(&self, _queue: &str, delivery_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some((_, pending)) = self.pending.remove(delivery_id) {
+            pending.acker.ack(BasicAckOptions::default()).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn nack(&self, _queue: &str, delivery_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if let Some((_, pending)) = self.pending.remove(delivery_id) {
+            // requeue: false - a terminal failure should dead-letter via the
+            // queue's configured DLX/TTL, not be redelivered forever.
+            pending
+                .acker
+                .nack(BasicNackOptions {
+                    requeue: false,
+                    ..Default::default()
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
 }
@@ -1,7 +1,12 @@
-use crate::brokers::core::BrokerCore;
+use crate::brokers::core::{BrokerCore, Delivery};
 use async_trait::async_trait;
 use redis::{AsyncCommands, Client, RedisResult};
 use std::sync::Arc;
+use uuid::Uuid;
+
+/// The consumer group every `RedisBroker` reads through, so in-flight
+/// messages left unacked by a crashed worker stay claimable by others.
+const CONSUMER_GROUP: &str = "fasttq_workers";
 
 #[derive(Clone)]
 pub struct RedisBroker {
@@ -16,17 +21,56 @@ impl RedisBroker {
             client: Arc::new(client),
         })
     }
+
+    fn stream_key(exchange: &str, routing_key: &str) -> String {
+        format!("{}:{}", exchange, routing_key)
+    }
 }
 
 #[async_trait]
 impl BrokerCore for RedisBroker {
+    async fn register_exchange(&self, _exchange: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // Redis has no concept of exchanges, so there is nothing to declare.
+        Ok(())
+    }
+
     async fn register_queue(
         &self,
-        _: &str,
-        _: &str,
-        _: &str,
+        exchange: &str,
+        queue: &str,
+        routing_key: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Redis does not have the concept of queues, so we don't need to do anything here
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let stream = Self::stream_key(exchange, routing_key);
+
+        // MKSTREAM creates the stream if it doesn't exist yet; BUSYGROUP
+        // just means another worker already created the group.
+        let result: RedisResult<()> = redis::cmd("XGROUP")
+            .arg("CREATE")
+            .arg(&stream)
+            .arg(CONSUMER_GROUP)
+            .arg("$")
+            .arg("MKSTREAM")
+            .query_async(&mut conn)
+            .await;
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) if e.to_string().contains("BUSYGROUP") => Ok(()),
+            Err(e) => {
+                let _ = queue;
+                Err(Box::new(e))
+            }
+        }
+    }
+
+    async fn delete_queue(&self, queue: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let _: () = conn.del(queue).await?;
+        Ok(())
+    }
+
+    async fn delete_exchange(&self, _exchange: &str) -> Result<(), Box<dyn std::error::Error>> {
         Ok(())
     }
 
@@ -35,14 +79,84 @@ impl BrokerCore for RedisBroker {
         exchange: &str,
         routing_key: &str,
         payload: &[u8],
+        message_id: &str,
+        task_id: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let mut conn = self.client.get_multiplexed_async_connection().await?;
-        let queue = format!("{}:{}", exchange, routing_key);
-        let result: RedisResult<()> = conn.publish(queue, payload).await;
+        let stream = Self::stream_key(exchange, routing_key);
+
+        let result: RedisResult<String> = redis::cmd("XADD")
+            .arg(&stream)
+            .arg("*")
+            .arg("payload")
+            .arg(payload)
+            .arg("message_id")
+            .arg(message_id)
+            .arg("task_id")
+            .arg(task_id)
+            .query_async(&mut conn)
+            .await;
 
         match result {
             Ok(_) => Ok(()),
             Err(e) => Err(Box::new(e)),
         }
     }
+
+    async fn consume(&self, queue: &str) -> Result<Delivery, Box<dyn std::error::Error>> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let consumer = Uuid::new_v4().to_string();
+
+        let reply: redis::streams::StreamReadReply = redis::cmd("XREADGROUP")
+            .arg("GROUP")
+            .arg(CONSUMER_GROUP)
+            .arg(&consumer)
+            .arg("COUNT")
+            .arg(1)
+            .arg("BLOCK")
+            .arg(5000)
+            .arg("STREAMS")
+            .arg(queue)
+            .arg(">")
+            .query_async(&mut conn)
+            .await?;
+
+        let entry = reply
+            .keys
+            .into_iter()
+            .next()
+            .and_then(|stream_key| stream_key.ids.into_iter().next())
+            .ok_or("no message available")?;
+
+        let payload = entry
+            .map
+            .get("payload")
+            .and_then(|value| match value {
+                redis::Value::BulkString(bytes) => Some(bytes.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        Ok(Delivery {
+            id: entry.id,
+            payload,
+        })
+    }
+
+    async fn ack(&self, queue: &str, delivery_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut conn = self.client.get_multiplexed_async_connection().await?;
+        let _: () = conn.xack(queue, CONSUMER_GROUP, &[delivery_id]).await?;
+        Ok(())
+    }
+
+    async fn nack(
+        &self,
+        _queue: &str,
+        _delivery_id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Leaving the entry unacked keeps it in the consumer group's pending
+        // entries list. `consume` only reads new entries (`>`), so this
+        // broker does not hand a pending entry out again.
+        Ok(())
+    }
 }
@@ -5,7 +5,8 @@ use crate::brokers::core::BrokerCore;
 use crate::brokers::Broker;
 use crate::TaskKind;
 use sqlx::types::Uuid;
-use crate::{Worker, TaskInstance, TaskStatus};
+use crate::{Worker, WorkerState, TaskInstance, TaskStatus};
+use crate::models::DEFAULT_MAX_CONCURRENT_TASKS;
 
 /// Mock implementations for BrokerCore that does nothing
 #[derive(Clone)]
@@ -80,21 +81,24 @@ pub fn setup_workers(task_kinds: Vec<TaskKind>) -> Vec<Worker> {
             name: "worker1".to_string(),
             registered_at: OffsetDateTime::now_utc(),
             task_kind: vec![task_kinds[0].clone()],
-            active: true,
+            state: WorkerState::Active,
+            max_concurrent_tasks: DEFAULT_MAX_CONCURRENT_TASKS,
         },
         Worker {
             id: Uuid::new_v4(),
             name: "worker2".to_string(),
             registered_at: OffsetDateTime::now_utc(),
             task_kind: vec![task_kinds[1].clone()],
-            active: true,
+            state: WorkerState::Active,
+            max_concurrent_tasks: DEFAULT_MAX_CONCURRENT_TASKS,
         },
         Worker {
             id: Uuid::new_v4(),
             name: "worker3".to_string(),
             registered_at: OffsetDateTime::now_utc(),
             task_kind: task_kinds,
-            active: true,
+            state: WorkerState::Active,
+            max_concurrent_tasks: DEFAULT_MAX_CONCURRENT_TASKS,
         },
     ]
 }
@@ -20,20 +20,46 @@ use crate::models::TaskKind;
 /// * `Timeout`: Exceeded time limit
 /// * `Rejected`: Worker refused task
 /// * `Blocked`: Waiting on dependencies
-#[derive(Display, Debug, Serialize, Deserialize, PartialEq, ToSchema)]
+/// * `DeadLetter`: Failed and exhausted all retry attempts
+#[derive(Display, Debug, Clone, Serialize, Deserialize, PartialEq, ToSchema)]
 pub enum TaskStatus {
-    Pending,   // Task is created but not yet assigned
-    Accepted,  // Worker acknowledged receipt
-    Queued,    // Task has been assigned to a worker and sent to a queue
-    Running,   // Worker has started processing
-    Paused,    // Temporarily suspended
-    Retrying,  // Failed but attempting again
-    Completed, // Task completed successfully
-    Failed,    // Task failed to complete
-    Cancelled, // Task was cancelled before completion
-    Timeout,   // Exceeded time limit
-    Rejected,  // Worker refused task
-    Blocked,   // Waiting on dependencies
+    Pending,    // Task is created but not yet assigned
+    Accepted,   // Worker acknowledged receipt
+    Queued,     // Task has been assigned to a worker and sent to a queue
+    Running,    // Worker has started processing
+    Paused,     // Temporarily suspended
+    Retrying,   // Failed but attempting again
+    Completed,  // Task completed successfully
+    Failed,     // Task failed to complete
+    Cancelled,  // Task was cancelled before completion
+    Timeout,    // Exceeded time limit
+    Rejected,   // Worker refused task
+    Blocked,    // Waiting on dependencies
+    DeadLetter, // Failed and exhausted all retry attempts
+}
+
+impl TaskStatus {
+    /// Whether a task currently in `self` may transition to `next`.
+    ///
+    /// `Completed`, `Failed`, `Cancelled`, `Rejected`, and `DeadLetter` are
+    /// terminal and accept no further transitions. Callers that accept a
+    /// status report over the network (e.g. `update_task_status` handlers)
+    /// should check this before writing, and reject illegal transitions
+    /// with a `409 Conflict` instead of applying them unconditionally.
+    pub fn can_transition_to(&self, next: TaskStatus) -> bool {
+        use TaskStatus::*;
+        match self {
+            Pending => matches!(next, Queued | Cancelled),
+            Queued => matches!(next, Running | Accepted | Cancelled),
+            Accepted => matches!(next, Running | Cancelled),
+            Running => matches!(next, Completed | Failed | Cancelled | Timeout | Paused),
+            Paused => matches!(next, Running | Cancelled),
+            Retrying => matches!(next, Queued | Running | Cancelled),
+            Timeout => matches!(next, Failed | Retrying | Cancelled),
+            Blocked => matches!(next, Queued | Cancelled),
+            Completed | Failed | Cancelled | Rejected | DeadLetter => false,
+        }
+    }
 }
 
 impl From<String> for TaskStatus {
@@ -62,6 +88,7 @@ impl TryFrom<&str> for TaskStatus {
             "timeout" => Ok(Self::Timeout),
             "rejected" => Ok(Self::Rejected),
             "blocked" => Ok(Self::Blocked),
+            "dead_letter" => Ok(Self::DeadLetter),
             _ => Err(format!("Invalid task status: {}", s)),
         }
     }
@@ -82,6 +109,7 @@ impl From<TaskStatus> for String {
             TaskStatus::Timeout => "timeout",
             TaskStatus::Rejected => "rejected",
             TaskStatus::Blocked => "blocked",
+            TaskStatus::DeadLetter => "dead_letter",
         }
         .to_string()
     }
@@ -116,3 +144,21 @@ pub struct TaskResult {
     pub worker_id: Uuid,
     pub created_at: OffsetDateTime,
 }
+
+/// A single recorded failure of a task, kept independently of `task_results`
+/// so a task's failure history survives even once it's retried and
+/// eventually completes. One row is written per failed attempt.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct TaskError {
+    pub task_id: Uuid,
+    pub worker_id: Uuid,
+    pub message: String,
+    /// Which retry attempt this failure was, matching `tasks.retries` at the
+    /// time of the failure.
+    pub retry_count: i32,
+    #[serde(
+        serialize_with = "crate::models::serialize_datetime",
+        deserialize_with = "crate::models::deserialize_datetime"
+    )]
+    pub occurred_at: OffsetDateTime,
+}
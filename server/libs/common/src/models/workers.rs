@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use strum_macros::Display;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
@@ -7,6 +8,63 @@ use time::OffsetDateTime;
 use super::TaskInstance;
 use crate::models::TaskKind;
 
+/// A worker's lifecycle state.
+///
+/// # Possible states:
+/// * `Registered`: Has registered its capabilities but hasn't been marked
+///   active yet (e.g. hasn't sent its first heartbeat).
+/// * `Active`: Eligible to be assigned new work.
+/// * `Draining`: Shutting down gracefully - finishing any task already
+///   assigned to it, but no longer eligible for new assignments.
+/// * `Offline`: Not eligible for work, whether by reaping, a clean
+///   shutdown, or manual deactivation.
+#[derive(Display, Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, ToSchema)]
+pub enum WorkerState {
+    Registered,
+    Active,
+    Draining,
+    Offline,
+}
+
+impl From<String> for WorkerState {
+    fn from(s: String) -> Self {
+        s.to_lowercase()
+            .as_str()
+            .try_into()
+            .unwrap_or_else(|_| panic!("Invalid worker state: {}", s))
+    }
+}
+
+impl TryFrom<&str> for WorkerState {
+    type Error = String;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "registered" => Ok(Self::Registered),
+            "active" => Ok(Self::Active),
+            "draining" => Ok(Self::Draining),
+            "offline" => Ok(Self::Offline),
+            _ => Err(format!("Invalid worker state: {}", s)),
+        }
+    }
+}
+
+impl From<WorkerState> for String {
+    fn from(state: WorkerState) -> Self {
+        match state {
+            WorkerState::Registered => "registered",
+            WorkerState::Active => "active",
+            WorkerState::Draining => "draining",
+            WorkerState::Offline => "offline",
+        }
+        .to_string()
+    }
+}
+
+/// Default [`Worker::max_concurrent_tasks`] for a worker that doesn't
+/// declare one at registration time.
+pub const DEFAULT_MAX_CONCURRENT_TASKS: i32 = 1;
+
 /// A worker that can execute tasks after receiving them.
 /// We know that it can receive those tasks from its list of capabilities.
 /// A worker must register itself with its capabilities to be able to receive tasks.
@@ -17,7 +75,11 @@ pub struct Worker {
     #[serde(serialize_with = "crate::models::serialize_datetime")]
     pub registered_at: OffsetDateTime,
     pub task_kind: Vec<TaskKind>,
-    pub active: bool,
+    pub state: WorkerState,
+    /// How many tasks this worker may run at once. Used by
+    /// `find_available_worker_for_kind` to pick a worker that isn't already
+    /// saturated.
+    pub max_concurrent_tasks: i32,
 }
 
 impl Worker {
@@ -27,7 +89,8 @@ impl Worker {
             name,
             registered_at: OffsetDateTime::now_utc(),
             task_kind,
-            active: true,
+            state: WorkerState::Active,
+            max_concurrent_tasks: DEFAULT_MAX_CONCURRENT_TASKS,
         }
     }
 
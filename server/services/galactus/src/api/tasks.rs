@@ -2,7 +2,7 @@ use std::{alloc::System, time::SystemTime};
 
 use axum::{
     extract::{Json, Path, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     routing::{get, post, put},
     Router,
 };
@@ -11,10 +11,13 @@ use tracing::{error, info};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-use common::{models::TaskInstance, TaskKind, TaskStatus};
+use common::{
+    models::{TaskError, TaskInstance, TaskResult},
+    TaskKind, TaskStatus,
+};
 
 use crate::{
-    repo::{TaskInstanceRepository, TaskKindRepository},
+    repo::{TaskErrorRepository, TaskInstanceRepository, TaskKindRepository, WorkerRepository},
     AppState,
 };
 
@@ -23,6 +26,8 @@ pub fn routes() -> Router<AppState> {
         .route("/:id", get(get_task_by_id))
         .route("/", post(create_task))
         .route("/:id/status", put(update_task_status))
+        .route("/:id/result", post(upload_task_result).get(get_task_result))
+        .route("/:id/errors", get(get_task_errors))
 }
 
 /// Get a task by its UUID
@@ -135,6 +140,68 @@ async fn create_task(
     Ok(Json(task))
 }
 
+/// Authorizes a worker-only request.
+///
+/// Requires an `X-Worker-Id` header matching `expected_worker_id` (e.g. the
+/// task's `assigned_to` worker, or the worker id in the request path), and
+/// an `Authorization: Bearer <token>` header carrying that worker's own
+/// registration token, as issued by `register_worker`. Returns 401 if the
+/// token is missing or doesn't match that worker, 403 if the caller isn't
+/// the worker `expected_worker_id` names. Verifying the token per-worker
+/// (rather than a single shared secret) stops one worker from acting as
+/// another just by guessing its id.
+pub(crate) async fn authorize_worker(
+    headers: &HeaderMap,
+    state: &AppState,
+    expected_worker_id: Option<Uuid>,
+) -> Result<(), (StatusCode, String)> {
+    let worker_id = headers
+        .get("X-Worker-Id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Uuid::parse_str(v).ok())
+        .ok_or((
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid X-Worker-Id header".to_string(),
+        ))?;
+
+    if expected_worker_id != Some(worker_id) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Worker is not assigned to this task".to_string(),
+        ));
+    }
+
+    let provided_token = headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .ok_or((
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid worker credentials".to_string(),
+        ))?;
+
+    let valid = state
+        .worker_repository
+        .verify_worker_token(&worker_id, provided_token)
+        .await
+        .map_err(|e| {
+            error!("Failed to verify worker token: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to verify worker token: {}", e),
+            )
+        })?;
+
+    if !valid {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid worker credentials".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 /// Update the status of a task
 ///
 /// # Arguments
@@ -153,7 +220,10 @@ async fn create_task(
     responses(
         (status = 200, description = "Task status updated"),
         (status = 400, description = "Invalid task status", content_type = "text/plain"),
+        (status = 401, description = "Missing or invalid worker credentials", content_type = "text/plain"),
+        (status = 403, description = "Worker is not assigned to this task", content_type = "text/plain"),
         (status = 404, description = "Task not found", content_type = "text/plain"),
+        (status = 409, description = "Illegal status transition", content_type = "text/plain"),
         (status = 500, description = "Internal server error", content_type = "text/plain")
     ),
     tag = "tasks"
@@ -161,6 +231,7 @@ async fn create_task(
 async fn update_task_status(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(task_status): Json<String>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     info!("Updating task status for task: {:?}", id);
@@ -185,6 +256,18 @@ async fn update_task_status(
             )
         })?;
 
+    authorize_worker(&headers, &state, task.assigned_to).await?;
+
+    if !task.status.can_transition_to(status.clone()) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Cannot transition task from {} to {}",
+                task.status, status
+            ),
+        ));
+    }
+
     state
         .task_repository
         .update_task_status(&task.id, status)
@@ -199,3 +282,179 @@ async fn update_task_status(
 
     Ok(StatusCode::OK)
 }
+
+/// Input for reporting a finished task's result.
+#[derive(Debug, Deserialize, ToSchema)]
+struct TaskResultInput {
+    output: Option<serde_json::Value>,
+    error: Option<String>,
+}
+
+/// Report the result of a finished task
+///
+/// Stores a successful output via [`TaskInstanceRepository::upload_task_result`],
+/// or an error via [`TaskInstanceRepository::upload_task_error`] (which
+/// retries with backoff if the task has attempts left) depending on whether
+/// `error` is set.
+///
+/// # Arguments
+/// * `id` - UUID of the task to report a result for
+#[utoipa::path(
+    post,
+    description = "Report the result of a finished task. This should only be used by workers.",
+    path = "/tasks/:id/result",
+    params(
+        ("id" = Uuid, Path, description = "Task ID to report a result for")
+    ),
+    request_body = TaskResultInput,
+    responses(
+        (status = 200, description = "Result recorded", body = TaskResult, content_type = "application/json"),
+        (status = 401, description = "Missing or invalid worker credentials", content_type = "text/plain"),
+        (status = 403, description = "Worker is not assigned to this task", content_type = "text/plain"),
+        (status = 404, description = "Task not found", content_type = "text/plain"),
+        (status = 500, description = "Internal server error", content_type = "text/plain")
+    ),
+    tag = "tasks"
+)]
+async fn upload_task_result(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(input): Json<TaskResultInput>,
+) -> Result<Json<TaskResult>, (StatusCode, String)> {
+    info!("Uploading result for task: {:?}", id);
+
+    let task = state
+        .task_repository
+        .get_task_by_id(&id, false)
+        .await
+        .map_err(|e| {
+            error!("Task not found: {:?}", e);
+            (
+                StatusCode::NOT_FOUND,
+                format!("Task with id {} not found", id),
+            )
+        })?;
+
+    authorize_worker(&headers, &state, task.assigned_to).await?;
+
+    // authorize_worker already confirmed assigned_to matches the caller, so
+    // it's always Some here.
+    let worker_id = task.assigned_to.unwrap();
+
+    let result = if let Some(message) = input.error {
+        state
+            .task_repository
+            .upload_task_error(&task.id, &worker_id, serde_json::json!({ "message": message }))
+            .await
+    } else {
+        state
+            .task_repository
+            .upload_task_result(
+                &task.id,
+                &worker_id,
+                input.output.unwrap_or(serde_json::Value::Null),
+            )
+            .await
+    }
+    .map_err(|e| {
+        error!("Failed to upload task result: {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to upload task result: {}", e),
+        )
+    })?;
+
+    Ok(Json(result))
+}
+
+/// Get the stored result for a task
+///
+/// # Arguments
+/// * `id` - UUID of the task to get the result for
+#[utoipa::path(
+    get,
+    description = "Get the stored result for a task",
+    path = "/tasks/:id/result",
+    params(
+        ("id" = Uuid, Path, description = "Task ID to get the result for")
+    ),
+    responses(
+        (status = 200, description = "Result found", body = TaskResult, content_type = "application/json"),
+        (status = 404, description = "Task not found, or it has no result yet", content_type = "text/plain"),
+        (status = 500, description = "Internal server error", content_type = "text/plain")
+    ),
+    tag = "tasks"
+)]
+async fn get_task_result(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TaskResult>, (StatusCode, String)> {
+    let task = state
+        .task_repository
+        .get_task_by_id(&id, true)
+        .await
+        .map_err(|e| {
+            error!("Task not found: {:?}", e);
+            (
+                StatusCode::NOT_FOUND,
+                format!("Task with id {} not found", id),
+            )
+        })?;
+
+    task.result.map(Json).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!("No result recorded yet for task {}", id),
+        )
+    })
+}
+
+/// Get the recorded failure history for a task
+///
+/// Returns every failed attempt recorded for the task, oldest first, even
+/// ones that were later retried successfully. An empty array means the task
+/// exists but has never failed.
+///
+/// # Arguments
+/// * `id` - UUID of the task to get the failure history for
+#[utoipa::path(
+    get,
+    description = "Get the recorded failure history for a task",
+    path = "/tasks/:id/errors",
+    params(
+        ("id" = Uuid, Path, description = "Task ID to get the failure history for")
+    ),
+    responses(
+        (status = 200, description = "Failure history", body = Vec<TaskError>, content_type = "application/json"),
+        (status = 404, description = "Task not found", content_type = "text/plain"),
+        (status = 500, description = "Internal server error", content_type = "text/plain")
+    ),
+    tag = "tasks"
+)]
+async fn get_task_errors(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<TaskError>>, (StatusCode, String)> {
+    state.task_repository.get_task_by_id(&id, false).await.map_err(|e| {
+        error!("Task not found: {:?}", e);
+        (
+            StatusCode::NOT_FOUND,
+            format!("Task with id {} not found", id),
+        )
+    })?;
+
+    let errors = state
+        .task_error_repository
+        .get_errors_for_task(&id)
+        .await
+        .map_err(|e| {
+            error!("Failed to get task errors: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to get task errors: {}", e),
+            )
+        })?;
+
+    Ok(Json(errors))
+}
@@ -1,9 +1,13 @@
+use std::convert::Infallible;
+
 use axum::{
     extract::{Json, Path, State},
-    http::StatusCode,
-    routing::{delete, post, put},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, Sse},
+    routing::{delete, get, post, put},
     Router,
 };
+use futures_util::stream::{self, Stream};
 use serde::Deserialize;
 use tracing::{error, info};
 use utoipa::ToSchema;
@@ -11,6 +15,7 @@ use uuid::Uuid;
 
 use common::models::Worker;
 
+use crate::api::tasks::authorize_worker;
 use crate::repo::{TaskKindRepository, WorkerRepository};
 use crate::AppState;
 
@@ -18,7 +23,8 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", post(register_worker))
         .route("/:id", delete(unregister_worker))
-    // .route("/workers/:id/heartbeat", put(heartbeat))
+        .route("/:id/jobs", get(stream_jobs))
+        .route("/:id/heartbeat", put(heartbeat))
 }
 
 /// Input data for creating a task
@@ -26,21 +32,40 @@ pub fn routes() -> Router<AppState> {
 struct RegisterWorkerInput {
     name: String,
     task_kinds: Vec<String>,
+    /// How many tasks this worker can run at once. Defaults to
+    /// [`common::models::DEFAULT_MAX_CONCURRENT_TASKS`] if omitted.
+    #[serde(default)]
+    max_concurrent_tasks: Option<i32>,
+}
+
+/// Response returned from worker registration: the worker, plus its freshly
+/// issued registration token. The token is only ever returned here; the
+/// server stores just its hash, so it cannot be recovered afterwards, only
+/// reissued by registering again.
+#[derive(Debug, serde::Serialize, Deserialize, ToSchema)]
+struct RegisterWorkerOutput {
+    #[serde(flatten)]
+    worker: Worker,
+    token: String,
 }
+
 /// Register a new worker
 ///
 /// # Arguments
 /// * `input` - Input data for registering a worker
 ///
 /// # Returns
-/// Returns a JSON response containing the registered worker
+/// Returns a JSON response containing the registered worker and its
+/// registration token. The worker must present this token as
+/// `Authorization: Bearer <token>` (with a matching `X-Worker-Id` header) on
+/// every worker-only endpoint afterwards.
 #[utoipa::path(
     post,
     description = "Register a new worker",
     path = "/workers",
     request_body = RegisterWorkerInput,
     responses(
-        (status = 200, description = "Worker registered", body = Worker, content_type = "application/json"),
+        (status = 201, description = "Worker registered", body = RegisterWorkerOutput, content_type = "application/json"),
         (status = 500, description = "Internal server error", content_type = "text/plain")
     ),
     tag = "workers"
@@ -48,7 +73,7 @@ struct RegisterWorkerInput {
 async fn register_worker(
     State(state): State<AppState>,
     Json(input): Json<RegisterWorkerInput>,
-) -> Result<(StatusCode, Json<Worker>), (StatusCode, String)> {
+) -> Result<(StatusCode, Json<RegisterWorkerOutput>), (StatusCode, String)> {
     info!("Registering worker with name: {:?}", input.name);
 
     // Get/create task kinds
@@ -73,7 +98,7 @@ async fn register_worker(
     // Register worker in repository
     let worker = state
         .worker_repository
-        .register_worker(id.clone(), input.name, task_kinds)
+        .register_worker(id.clone(), input.name, task_kinds, input.max_concurrent_tasks)
         .await
         .map_err(|e| {
             error!("Failed to register worker: {:?}", e);
@@ -97,7 +122,19 @@ async fn register_worker(
             )
         })?;
 
-    Ok((StatusCode::CREATED, Json(worker)))
+    let token = state
+        .worker_repository
+        .issue_worker_token(&worker.id)
+        .await
+        .map_err(|e| {
+            error!("Failed to issue worker token: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to issue worker token: {}", e),
+            )
+        })?;
+
+    Ok((StatusCode::CREATED, Json(RegisterWorkerOutput { worker, token })))
 }
 
 /// Unregister an existing worker
@@ -116,6 +153,8 @@ async fn register_worker(
     ),
     responses(
         (status = 200, description = "Worker unregistered", content_type = "application/json"),
+        (status = 401, description = "Missing or invalid worker credentials", content_type = "text/plain"),
+        (status = 403, description = "X-Worker-Id does not match the path worker id", content_type = "text/plain"),
         (status = 404, description = "Worker not found", content_type = "text/plain"),
         (status = 500, description = "Internal server error", content_type = "text/plain")
     ),
@@ -124,9 +163,12 @@ async fn register_worker(
 async fn unregister_worker(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
 ) -> Result<StatusCode, (StatusCode, String)> {
     info!("Unregistering worker with ID: {:?}", id);
 
+    authorize_worker(&headers, &state, Some(id)).await?;
+
     // Set worker as inactive in repository
     state
         .worker_repository
@@ -157,6 +199,103 @@ async fn unregister_worker(
 
     Ok(StatusCode::OK)
 }
+
+/// Open a long-lived connection a worker holds to pull jobs.
+///
+/// Streams one `job` event per task the dispatcher claims for this worker.
+/// The task only moves out of `Pending` once it's actually handed to this
+/// connection, so `create_task` never fails just because no worker happened
+/// to be connected at submission time.
+///
+/// # Arguments
+/// * `id` - UUID of the connecting worker
+#[utoipa::path(
+    get,
+    description = "Open a long-lived connection a worker holds to pull jobs",
+    path = "/workers/{id}/jobs",
+    params(
+        ("id" = Uuid, Path, description = "UUID of the connecting worker")
+    ),
+    responses(
+        (status = 200, description = "Job stream opened", content_type = "text/event-stream"),
+        (status = 401, description = "Missing or invalid worker credentials", content_type = "text/plain"),
+        (status = 403, description = "X-Worker-Id does not match the path worker id", content_type = "text/plain")
+    ),
+    tag = "workers"
+)]
+async fn stream_jobs(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    authorize_worker(&headers, &state, Some(id)).await?;
+
+    info!("Worker {:?} connected for jobs", id);
+
+    let rx = state.job_dispatcher.connect(id).await;
+
+    let stream = stream::unfold(rx, |mut rx| async move {
+        let job = rx.recv().await?;
+        let event = Event::default()
+            .event("job")
+            .json_data(&job)
+            .unwrap_or_else(|e| {
+                error!("Failed to serialize job for worker stream: {:?}", e);
+                Event::default().event("job")
+            });
+        Some((Ok(event), rx))
+    });
+
+    Ok(Sse::new(stream))
+}
+
+/// Record a liveness heartbeat for a worker.
+///
+/// Workers should call this periodically while idle or between jobs; the
+/// background reaper reclaims a worker's in-flight tasks and marks it
+/// inactive once it goes too long without one. Gated the same way as the
+/// worker-only task routes: a valid bearer secret plus an `X-Worker-Id`
+/// header matching the path id.
+///
+/// # Arguments
+/// * `id` - UUID of the worker sending the heartbeat
+#[utoipa::path(
+    put,
+    description = "Record a liveness heartbeat for a worker",
+    path = "/workers/{id}/heartbeat",
+    params(
+        ("id" = Uuid, Path, description = "UUID of the worker sending the heartbeat")
+    ),
+    responses(
+        (status = 200, description = "Heartbeat recorded"),
+        (status = 401, description = "Missing or invalid worker credentials", content_type = "text/plain"),
+        (status = 403, description = "X-Worker-Id does not match the path worker id", content_type = "text/plain"),
+        (status = 500, description = "Internal server error", content_type = "text/plain")
+    ),
+    tag = "workers"
+)]
+async fn heartbeat(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    authorize_worker(&headers, &state, Some(id)).await?;
+
+    state
+        .worker_repository
+        .record_heartbeat(&id)
+        .await
+        .map_err(|e| {
+            error!("Failed to record worker heartbeat: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to record worker heartbeat: {}", e),
+            )
+        })?;
+
+    Ok(StatusCode::OK)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -199,11 +338,13 @@ mod test {
             .await;
 
         assert_eq!(response.status_code(), StatusCode::CREATED);
-        let worker: Worker = serde_json::from_str(&response.text()).unwrap();
-        assert_eq!(worker.name, "test_worker");
+        let registered: RegisterWorkerOutput = serde_json::from_str(&response.text()).unwrap();
+        assert_eq!(registered.worker.name, "test_worker");
+        assert!(!registered.token.is_empty());
     }
 
-    // Test unregistering an existing worker
+    // Test unregistering an existing worker, presenting its registration
+    // token from the registration response
     #[sqlx::test(migrator = "db_common::MIGRATOR")]
     async fn test_unregister_worker_success(db_pools: PgPool) {
         let mut broker = get_mock_broker();
@@ -213,20 +354,29 @@ mod test {
         let core = PgRepositoryCore::new(db_pools.clone());
         let worker_repo = PgWorkerRepository::new(core);
         worker_repo
-            .register_worker(test_worker.id, test_worker.name, test_worker.task_kind)
+            .register_worker(test_worker.id, test_worker.name, test_worker.task_kind, None)
+            .await
+            .unwrap();
+        let token = worker_repo
+            .issue_worker_token(&test_worker.id)
             .await
             .unwrap();
 
         let server = get_test_server(db_pools, broker).await;
 
-        let response = server.delete(&format!("/workers/{}", test_worker.id)).await;
+        let response = server
+            .delete(&format!("/workers/{}", test_worker.id))
+            .add_header("X-Worker-Id", test_worker.id.to_string())
+            .add_header("Authorization", format!("Bearer {}", token))
+            .await;
 
         assert_eq!(response.status_code(), StatusCode::OK);
     }
 
-    // Test unregistering a non-existent worker
+    // Unregistering without a valid worker token is rejected before the
+    // server even checks whether the worker exists
     #[sqlx::test(migrator = "db_common::MIGRATOR")]
-    async fn test_unregister_nonexistent_worker(db_pools: PgPool) {
+    async fn test_unregister_worker_requires_valid_token(db_pools: PgPool) {
         let broker = get_mock_broker();
         let server = get_test_server(db_pools, broker).await;
 
@@ -234,6 +384,20 @@ mod test {
             .delete("/workers/123e4567-e89b-12d3-a456-426614174000")
             .await;
 
-        assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
+    // Connecting to a worker's job stream without a valid worker token is
+    // rejected before the server opens the SSE connection
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn test_stream_jobs_requires_valid_token(db_pools: PgPool) {
+        let broker = get_mock_broker();
+        let server = get_test_server(db_pools, broker).await;
+
+        let response = server
+            .get("/workers/123e4567-e89b-12d3-a456-426614174000/jobs")
+            .await;
+
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
     }
 }
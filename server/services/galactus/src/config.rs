@@ -1,11 +1,52 @@
 use dotenv::dotenv;
 
+use crate::repo::RetentionMode;
+
 pub struct Config {
     pub broker_addr: String,
     pub db_reader_url: String,
     pub db_writer_url: String,
+    /// How long a worker may go without a heartbeat before the reaper
+    /// considers it dead. Set via `FASTTQ_WORKER_HEARTBEAT_TTL_SECS`.
+    pub worker_heartbeat_ttl_secs: u64,
+    /// How often the reaper scans for workers past their heartbeat TTL.
+    pub worker_reap_interval_secs: u64,
+    /// Path to a PEM-encoded TLS certificate (chain) to serve the API over
+    /// HTTPS. Must be set together with `tls_key_path`; if either is unset
+    /// the server falls back to plain HTTP.
+    pub tls_cert_path: Option<String>,
+    /// Path to the PEM-encoded private key matching `tls_cert_path`.
+    pub tls_key_path: Option<String>,
+    /// Port the HTTP(S) listener binds to. Set via `FASTTQ_PORT`.
+    pub port: u16,
+    /// Which terminal tasks the retention sweeper deletes. Set via
+    /// `FASTTQ_RETENTION_MODE` (`keep_all`, `remove_all`, `remove_failed`,
+    /// `remove_done`).
+    pub retention_mode: RetentionMode,
+    /// How old a terminal task must be before the retention sweeper removes
+    /// it. Set via `FASTTQ_RETENTION_MAX_AGE_SECS`.
+    pub retention_max_age_secs: u64,
+    /// On SIGINT/SIGTERM, how long to let in-flight requests and background
+    /// sweeps finish before forcing the process to exit. Set via
+    /// `FASTTQ_SHUTDOWN_DRAIN_TIMEOUT_SECS`.
+    pub shutdown_drain_timeout_secs: u64,
 }
 
+/// Default for [`Config::worker_heartbeat_ttl_secs`] when
+/// `FASTTQ_WORKER_HEARTBEAT_TTL_SECS` isn't set.
+const DEFAULT_WORKER_HEARTBEAT_TTL_SECS: u64 = 90;
+/// Default for [`Config::worker_reap_interval_secs`] when
+/// `FASTTQ_WORKER_REAP_INTERVAL_SECS` isn't set.
+const DEFAULT_WORKER_REAP_INTERVAL_SECS: u64 = 30;
+/// Default for [`Config::port`] when `FASTTQ_PORT` isn't set.
+const DEFAULT_PORT: u16 = 3000;
+/// Default for [`Config::retention_max_age_secs`] when
+/// `FASTTQ_RETENTION_MAX_AGE_SECS` isn't set: 7 days.
+const DEFAULT_RETENTION_MAX_AGE_SECS: u64 = 60 * 60 * 24 * 7;
+/// Default for [`Config::shutdown_drain_timeout_secs`] when
+/// `FASTTQ_SHUTDOWN_DRAIN_TIMEOUT_SECS` isn't set.
+const DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_SECS: u64 = 30;
+
 fn load_env() {
     // Load only in development
     if cfg!(debug_assertions) {
@@ -13,6 +54,17 @@ fn load_env() {
     }
 }
 
+/// Parses `FASTTQ_RETENTION_MODE`, defaulting to `RemoveDone` (the
+/// historical, hardcoded behavior) for an unset or unrecognized value.
+fn parse_retention_mode(value: Option<String>) -> RetentionMode {
+    match value.as_deref() {
+        Some("keep_all") => RetentionMode::KeepAll,
+        Some("remove_all") => RetentionMode::RemoveAll,
+        Some("remove_failed") => RetentionMode::RemoveFailed,
+        _ => RetentionMode::RemoveDone,
+    }
+}
+
 impl Config {
     pub fn new() -> Config {
         load_env();
@@ -21,6 +73,29 @@ impl Config {
             broker_addr: std::env::var("FASTTQ_BROKER_ADDR").unwrap(),
             db_reader_url: std::env::var("FASTTQ_DATABASE_READER_URL").unwrap(),
             db_writer_url: std::env::var("FASTTQ_DATABASE_WRITER_URL").unwrap(),
+            worker_heartbeat_ttl_secs: std::env::var("FASTTQ_WORKER_HEARTBEAT_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_WORKER_HEARTBEAT_TTL_SECS),
+            worker_reap_interval_secs: std::env::var("FASTTQ_WORKER_REAP_INTERVAL_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_WORKER_REAP_INTERVAL_SECS),
+            tls_cert_path: std::env::var("FASTTQ_TLS_CERT_PATH").ok(),
+            tls_key_path: std::env::var("FASTTQ_TLS_KEY_PATH").ok(),
+            port: std::env::var("FASTTQ_PORT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_PORT),
+            retention_mode: parse_retention_mode(std::env::var("FASTTQ_RETENTION_MODE").ok()),
+            retention_max_age_secs: std::env::var("FASTTQ_RETENTION_MAX_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_RETENTION_MAX_AGE_SECS),
+            shutdown_drain_timeout_secs: std::env::var("FASTTQ_SHUTDOWN_DRAIN_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_SHUTDOWN_DRAIN_TIMEOUT_SECS),
         }
     }
 }
@@ -0,0 +1,146 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use common::models::TaskInstance;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::repo::{TaskInstanceRepository, WorkerRepository};
+
+/// How often the dispatcher scans connected workers for a matching pending
+/// task.
+const DISPATCH_INTERVAL: Duration = Duration::from_millis(500);
+/// Capacity of each worker's job channel. The dispatcher hands a worker at
+/// most one job per tick, and only while its in-flight count is under its
+/// `max_concurrent_tasks`; the small buffer just keeps the dispatcher from
+/// blocking on a receiver that's briefly busy forwarding earlier jobs.
+const JOB_CHANNEL_CAPACITY: usize = 4;
+
+/// A task handed to a worker over its job channel.
+pub type RequestedJob = TaskInstance;
+
+/// Tracks workers that currently hold an open `/workers/:id/jobs` connection,
+/// so the dispatch loop has somewhere to push newly claimed tasks.
+///
+/// This is the pull-model counterpart to the broker: instead of `create_task`
+/// publishing work and hoping a worker is listening, workers long-poll for
+/// jobs and a task is only claimed once a worker is actually connected and
+/// under its concurrency cap, via
+/// [`TaskInstanceRepository::fetch_and_claim_task`].
+#[derive(Clone, Default)]
+pub struct JobDispatcher {
+    connected: Arc<Mutex<HashMap<Uuid, mpsc::Sender<RequestedJob>>>>,
+}
+
+impl JobDispatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `worker_id` as connected and returns the receiving half of
+    /// its job channel. A second connect for the same worker (e.g. a
+    /// reconnect) replaces the previous channel.
+    pub async fn connect(&self, worker_id: Uuid) -> mpsc::Receiver<RequestedJob> {
+        let (tx, rx) = mpsc::channel(JOB_CHANNEL_CAPACITY);
+        self.connected.lock().await.insert(worker_id, tx);
+        rx
+    }
+
+    /// Removes `worker_id` from the connected set, e.g. once its job stream
+    /// disconnects.
+    pub async fn disconnect(&self, worker_id: &Uuid) {
+        self.connected.lock().await.remove(worker_id);
+    }
+
+    async fn connected_worker_ids(&self) -> Vec<Uuid> {
+        self.connected.lock().await.keys().copied().collect()
+    }
+
+    async fn sender_for(&self, worker_id: &Uuid) -> Option<mpsc::Sender<RequestedJob>> {
+        self.connected.lock().await.get(worker_id).cloned()
+    }
+}
+
+/// Spawns a background loop that matches pending tasks to connected workers
+/// with spare capacity whose registered task kinds accept them, claiming each match
+/// atomically before handing it off over the worker's job channel.
+///
+/// `heartbeat_threshold` is forwarded to
+/// [`WorkerRepository::find_available_worker_for_kind`] and should match
+/// [`crate::config::Config::worker_heartbeat_ttl_secs`], so dispatch agrees
+/// with the reaper on how stale a heartbeat can be before a worker is no
+/// longer considered available.
+pub fn spawn_dispatch_loop<T, W>(
+    dispatcher: JobDispatcher,
+    task_repository: T,
+    worker_repository: W,
+    heartbeat_threshold: Duration,
+) where
+    T: TaskInstanceRepository + Send + Sync + 'static,
+    W: WorkerRepository + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DISPATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            for worker_id in dispatcher.connected_worker_ids().await {
+                let worker = match worker_repository.get_worker_by_id(&worker_id).await {
+                    Ok(worker) => worker,
+                    Err(err) => {
+                        error!(%worker_id, %err, "Dispatcher failed to look up connected worker");
+                        continue;
+                    }
+                };
+
+                for task_kind in &worker.task_kind {
+                    // find_available_worker_for_kind does the eligibility
+                    // check (Active, fresh heartbeat, under its concurrency
+                    // cap) and least-loaded selection in one locked query;
+                    // only proceed if it actually picked this connected
+                    // worker for this kind.
+                    let best = match worker_repository
+                        .find_available_worker_for_kind(task_kind.id, heartbeat_threshold)
+                        .await
+                    {
+                        Ok(Some(best)) => best,
+                        Ok(None) => continue,
+                        Err(err) => {
+                            error!(%worker_id, %err, "Dispatcher failed to find an available worker");
+                            continue;
+                        }
+                    };
+                    if best.id != worker_id {
+                        continue;
+                    }
+
+                    // Check the worker is still connected before claiming a
+                    // task for it - otherwise a disconnect landing in this
+                    // window strands an already-claimed task until the
+                    // heartbeat-TTL reaper notices and requeues it.
+                    let Some(sender) = dispatcher.sender_for(&worker_id).await else {
+                        break;
+                    };
+
+                    let task = match task_repository
+                        .fetch_and_claim_task(&worker_id, &[task_kind.id])
+                        .await
+                    {
+                        Ok(Some(task)) => task,
+                        Ok(None) => continue,
+                        Err(err) => {
+                            error!(%worker_id, %err, "Dispatcher failed to claim a task");
+                            continue;
+                        }
+                    };
+
+                    if sender.send(task).await.is_err() {
+                        info!(%worker_id, "Worker disconnected before its job could be delivered");
+                        dispatcher.disconnect(&worker_id).await;
+                    }
+                    break;
+                }
+            }
+        }
+    });
+}
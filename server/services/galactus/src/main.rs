@@ -1,18 +1,29 @@
 mod api;
 mod config;
+mod dispatch;
 mod repo;
 mod testing;
 
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
 
 use axum::Router;
 use common::brokers::Broker;
-use sqlx::PgPool;
+use common::db::pools::DatabasePools;
 use tokio::sync::RwLock;
-use tracing::info;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
 
 use config::Config;
-use repo::{PgRepositoryCore, PgTaskInstanceRepository, PgTaskKindRepository, PgWorkerRepository};
+use dispatch::JobDispatcher;
+use repo::{
+    PgRepositoryCore, PgTaskErrorRepository, PgTaskInstanceRepository, PgTaskKindRepository,
+    PgWorkerRepository, RetentionMode, TaskInstanceRepository,
+};
+
+/// How often the retention sweeper runs.
+const RETENTION_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How often the cron scheduler checks for stalled recurring chains.
+const CRON_SCHEDULER_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Initializes the logger
 async fn setup_logger() {
@@ -35,16 +46,27 @@ pub struct AppState {
     pub task_repository: PgTaskInstanceRepository,
     pub task_kind_repository: PgTaskKindRepository,
     pub worker_repository: PgWorkerRepository,
+    /// Stores every failed attempt for a task, independent of its latest
+    /// `task_results` row.
+    pub task_error_repository: PgTaskErrorRepository,
     pub broker: Arc<RwLock<Broker>>,
+    /// Tracks workers long-polling for jobs at `/workers/:id/jobs`, so
+    /// pending tasks can be pushed to an idle worker as soon as one connects.
+    pub job_dispatcher: JobDispatcher,
+    /// How long a worker may go without a heartbeat before the reaper
+    /// considers it dead. Mirrors [`Config::worker_heartbeat_ttl_secs`].
+    pub worker_heartbeat_ttl_secs: u64,
 }
 
-/// Creates database connection pools
+/// Creates the reader/writer database connection pools
 ///
 /// # Arguments
 ///
 /// * `config` - The configuration for the database
-async fn setup_db_pools(config: &Config) -> PgPool {
-    PgPool::connect(&config.db_reader_url).await.unwrap()
+async fn setup_db_pools(config: &Config) -> DatabasePools {
+    DatabasePools::new(&config.db_reader_url, &config.db_writer_url)
+        .await
+        .unwrap()
 }
 
 /// Initializes the broker
@@ -64,30 +86,169 @@ async fn setup_broker(config: &Config) -> Broker {
 ///
 /// * `db_pools` - The database connection pools
 /// * `broker` - The broker
-async fn setup_app_state(db_pools: PgPool, broker: Broker) -> AppState {
+async fn setup_app_state(
+    db_pools: DatabasePools,
+    broker: Broker,
+    worker_heartbeat_ttl_secs: u64,
+) -> AppState {
     // Setup the repositories
-    let core = PgRepositoryCore::new(db_pools.clone());
+    let core = PgRepositoryCore::with_pools(db_pools.reader, db_pools.writer);
     let task_repository = PgTaskInstanceRepository::new(core.clone());
     let task_kind_repository = PgTaskKindRepository::new(core.clone());
     let worker_repository = PgWorkerRepository::new(core.clone());
+    let task_error_repository = PgTaskErrorRepository::new(core.clone());
+    let job_dispatcher = JobDispatcher::new();
+
+    dispatch::spawn_dispatch_loop(
+        job_dispatcher.clone(),
+        task_repository.clone(),
+        worker_repository.clone(),
+        Duration::from_secs(worker_heartbeat_ttl_secs),
+    );
 
     AppState {
         task_repository,
         task_kind_repository,
         worker_repository,
+        task_error_repository,
         broker: Arc::new(RwLock::new(broker)),
+        job_dispatcher,
+        worker_heartbeat_ttl_secs,
     }
 }
 
+/// Spawns a background task that periodically deletes old, terminal tasks
+/// (and their cascaded `task_results`) so the tables don't grow unbounded.
+/// `mode` and `max_age` are operator-tunable via [`Config::retention_mode`]
+/// and [`Config::retention_max_age_secs`].
+fn spawn_retention_sweeper(
+    task_repository: PgTaskInstanceRepository,
+    mode: RetentionMode,
+    max_age: Duration,
+    shutdown: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_SWEEP_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    info!("Retention sweeper shutting down");
+                    break;
+                }
+                _ = interval.tick() => {
+                    match task_repository.cleanup_finished_tasks(mode, max_age).await {
+                        Ok(deleted) => info!(deleted, "Retention sweep removed finished tasks"),
+                        Err(err) => error!(%err, "Retention sweep failed"),
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Spawns a background task that periodically re-seeds cron chains whose
+/// latest occurrence ended in a terminal-but-not-completed state, so a
+/// recurring task doesn't silently stop firing just because one occurrence
+/// failed, was cancelled, or dead-lettered.
+fn spawn_cron_scheduler(task_repository: PgTaskInstanceRepository, shutdown: CancellationToken) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CRON_SCHEDULER_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    info!("Cron scheduler shutting down");
+                    break;
+                }
+                _ = interval.tick() => {
+                    match task_repository.reseed_stalled_cron_tasks().await {
+                        Ok(reseeded) if reseeded > 0 => {
+                            info!(reseeded, "Reseeded stalled cron chains")
+                        }
+                        Ok(_) => {}
+                        Err(err) => error!(%err, "Cron scheduler sweep failed"),
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Spawns a background task that periodically reaps workers that have
+/// missed their heartbeat deadline. A reaped worker is marked inactive,
+/// removed from the broker, and any task still assigned to it is re-queued
+/// (or failed, if it's out of retries) so it isn't stuck forever waiting on
+/// a worker that crashed.
+fn spawn_worker_reaper(
+    worker_repository: PgWorkerRepository,
+    broker: Arc<RwLock<Broker>>,
+    heartbeat_ttl_secs: u64,
+    reap_interval_secs: u64,
+    shutdown: CancellationToken,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(reap_interval_secs));
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => {
+                    info!("Worker reaper shutting down");
+                    break;
+                }
+                _ = interval.tick() => {
+                    match worker_repository
+                        .reap_stale_workers(Duration::from_secs(heartbeat_ttl_secs))
+                        .await
+                    {
+                        Ok(reaped) => {
+                            for worker_id in &reaped {
+                                if let Err(err) = broker.write().await.remove_worker(worker_id).await {
+                                    error!(%err, %worker_id, "Failed to remove reaped worker from broker");
+                                }
+                            }
+                            if !reaped.is_empty() {
+                                info!(count = reaped.len(), "Reaped stale workers");
+                            }
+                        }
+                        Err(err) => error!(%err, "Worker reap sweep failed"),
+                    }
+                }
+            }
+        }
+    });
+}
+
 /// Initializes the application router
 ///
 /// # Arguments
 ///
 /// * `db_pools` - The database connection pools
 /// * `broker` - The broker
-async fn setup_app(db_pools: PgPool, broker: Broker) -> Router {
-    let app_state = setup_app_state(db_pools, broker).await;
+async fn setup_app(
+    db_pools: DatabasePools,
+    broker: Broker,
+    worker_heartbeat_ttl_secs: u64,
+    worker_reap_interval_secs: u64,
+    retention_mode: RetentionMode,
+    retention_max_age_secs: u64,
+    shutdown: CancellationToken,
+) -> Router {
+    let app_state = setup_app_state(db_pools, broker, worker_heartbeat_ttl_secs).await;
     info!("App state created");
+
+    spawn_retention_sweeper(
+        app_state.task_repository.clone(),
+        retention_mode,
+        Duration::from_secs(retention_max_age_secs),
+        shutdown.clone(),
+    );
+    spawn_cron_scheduler(app_state.task_repository.clone(), shutdown.clone());
+    spawn_worker_reaper(
+        app_state.worker_repository.clone(),
+        app_state.broker.clone(),
+        worker_heartbeat_ttl_secs,
+        worker_reap_interval_secs,
+        shutdown,
+    );
+
     Router::new().merge(api::routes()).with_state(app_state)
 }
 
@@ -105,14 +266,126 @@ async fn main() {
     let broker = setup_broker(&config).await;
     info!("Broker initialized");
 
-    let app = setup_app(db_pools, broker).await;
+    let shutdown = CancellationToken::new();
+    tokio::spawn(listen_for_shutdown_signal(shutdown.clone()));
+
+    let app = setup_app(
+        db_pools,
+        broker,
+        config.worker_heartbeat_ttl_secs,
+        config.worker_reap_interval_secs,
+        config.retention_mode,
+        config.retention_max_age_secs,
+        shutdown.clone(),
+    )
+    .await;
     info!("App created");
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 3000));
+    let addr = SocketAddr::from(([0, 0, 0, 0], config.port));
     info!("Listening on {}", addr);
 
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app.into_make_service())
-        .await
-        .unwrap();
+    serve(
+        app,
+        addr,
+        config.tls_cert_path,
+        config.tls_key_path,
+        shutdown,
+        Duration::from_secs(config.shutdown_drain_timeout_secs),
+    )
+    .await;
+}
+
+/// Waits for SIGINT (Ctrl+C) or, on Unix, SIGTERM, then cancels `shutdown`
+/// so the server stops accepting new connections and every background sweep
+/// loop stops claiming new work.
+async fn listen_for_shutdown_signal(shutdown: CancellationToken) {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    info!("Shutdown signal received, draining in-flight work");
+    shutdown.cancel();
+}
+
+/// Serves `app` on `addr`, over HTTPS if both `tls_cert_path` and
+/// `tls_key_path` are set, falling back to plain HTTP otherwise. Workers and
+/// clients exchange task payloads and worker registration over this
+/// connection, so TLS is recommended in any networked deployment.
+///
+/// Stops accepting new connections as soon as `shutdown` is cancelled, lets
+/// in-flight requests finish, and forces the listener closed after
+/// `drain_timeout` if any connection is still open by then.
+async fn serve(
+    app: Router,
+    addr: SocketAddr,
+    tls_cert_path: Option<String>,
+    tls_key_path: Option<String>,
+    shutdown: CancellationToken,
+    drain_timeout: Duration,
+) {
+    match (tls_cert_path, tls_key_path) {
+        (Some(cert_path), Some(key_path)) => {
+            info!("Serving over HTTPS using cert {}", cert_path);
+            let tls_config = axum_server::tls_rustls::RustlsConfig::from_pem_file(cert_path, key_path)
+                .await
+                .expect("Failed to load TLS cert/key");
+
+            let handle = axum_server::Handle::new();
+            tokio::spawn({
+                let handle = handle.clone();
+                async move {
+                    shutdown.cancelled().await;
+                    info!(?drain_timeout, "Draining HTTPS connections");
+                    handle.graceful_shutdown(Some(drain_timeout));
+                }
+            });
+
+            axum_server::bind_rustls(addr, tls_config)
+                .handle(handle)
+                .serve(app.into_make_service())
+                .await
+                .unwrap();
+        }
+        _ => {
+            let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
+            let serve_future = axum::serve(listener, app.into_make_service()).with_graceful_shutdown(
+                {
+                    let shutdown = shutdown.clone();
+                    async move {
+                        shutdown.cancelled().await;
+                        info!("Draining HTTP connections");
+                    }
+                },
+            );
+
+            tokio::pin!(serve_future);
+            tokio::select! {
+                result = &mut serve_future => result.unwrap(),
+                _ = async {
+                    shutdown.cancelled().await;
+                    tokio::time::sleep(drain_timeout).await;
+                } => {
+                    error!("Graceful shutdown drain timeout exceeded, forcing exit");
+                }
+            }
+        }
+    }
 }
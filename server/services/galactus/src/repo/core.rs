@@ -1,12 +1,35 @@
 use sqlx::PgPool;
 
+/// Shared connection handles every `Pg*Repository` is built on. Carries a
+/// `reader`/`writer` split so read-only queries can be routed to a replica
+/// while mutations (and anything that needs a transaction) go to the
+/// primary; callers that only have one database (tests, anything not
+/// configured with a replica) just hand the same pool to both.
 #[derive(Clone)]
 pub struct PgRepositoryCore {
-    pub pool: PgPool,
+    pub reader: PgPool,
+    pub writer: PgPool,
 }
 
 impl PgRepositoryCore {
+    /// A single pool used for both reads and writes.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            reader: pool.clone(),
+            writer: pool,
+        }
+    }
+
+    /// A genuine reader/writer split, for deployments where `reader` points
+    /// at a replica.
+    pub fn with_pools(reader: PgPool, writer: PgPool) -> Self {
+        Self { reader, writer }
+    }
+
+    /// Escape hatch for a read that must observe a write this same caller
+    /// just made: routes through `writer` instead of `reader`, since a
+    /// replica behind `reader` may not have caught up yet.
+    pub fn read_your_writes(&self) -> &PgPool {
+        &self.writer
     }
 }
@@ -1,7 +1,9 @@
-pub mod task_repo;
-pub mod task_type_repo;
+pub mod task_error_repo;
+pub mod task_instance_repo;
+pub mod task_kind_repo;
 pub mod worker_repo;
 
-pub use task_repo::PgTaskRepository;
-pub use task_type_repo::PgTaskTypeRepository;
+pub use task_error_repo::PgTaskErrorRepository;
+pub use task_instance_repo::PgTaskInstanceRepository;
+pub use task_kind_repo::PgTaskKindRepository;
 pub use worker_repo::PgWorkerRepository;
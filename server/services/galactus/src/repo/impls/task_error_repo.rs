@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use common::models::TaskError;
+use uuid::Uuid;
+
+use crate::repo::{PgRepositoryCore, TaskErrorRepository};
+
+#[derive(Clone)]
+pub struct PgTaskErrorRepository {
+    core: PgRepositoryCore,
+}
+
+impl PgTaskErrorRepository {
+    pub fn new(core: PgRepositoryCore) -> Self {
+        Self { core }
+    }
+}
+
+#[async_trait]
+impl TaskErrorRepository for PgTaskErrorRepository {
+    async fn record_error(
+        &self,
+        task_id: &Uuid,
+        worker_id: &Uuid,
+        message: &str,
+        retry_count: i32,
+    ) -> Result<TaskError, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO task_errors (task_id, worker_id, message, retry_count, occurred_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            RETURNING task_id, worker_id, message, retry_count, occurred_at
+            "#,
+            task_id,
+            worker_id,
+            message,
+            retry_count,
+        )
+        .fetch_one(&self.core.writer)
+        .await?;
+
+        Ok(TaskError {
+            task_id: row.task_id,
+            worker_id: row.worker_id,
+            message: row.message,
+            retry_count: row.retry_count,
+            occurred_at: row.occurred_at,
+        })
+    }
+
+    async fn get_errors_for_task(&self, task_id: &Uuid) -> Result<Vec<TaskError>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT task_id, worker_id, message, retry_count, occurred_at
+            FROM task_errors
+            WHERE task_id = $1
+            ORDER BY occurred_at ASC
+            "#,
+            task_id
+        )
+        .fetch_all(&self.core.reader)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| TaskError {
+                task_id: row.task_id,
+                worker_id: row.worker_id,
+                message: row.message,
+                retry_count: row.retry_count,
+                occurred_at: row.occurred_at,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        repo::{PgRepositoryCore, PgTaskKindRepository, PgTaskInstanceRepository, TaskInstanceRepository, TaskKindRepository},
+        testing::test::init_test_logger,
+    };
+    use sqlx::PgPool;
+
+    // This runs before any test in this module
+    #[ctor::ctor]
+    fn init() {
+        init_test_logger();
+    }
+
+    /// Records a couple of failures for a task and reads them back in order
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn record_and_list_errors(pool: PgPool) {
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgTaskErrorRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core.clone());
+        let task_repo = PgTaskInstanceRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+        let task = task_repo
+            .create_task(task_kind.id, None)
+            .await
+            .unwrap();
+        let worker_id = Uuid::new_v4();
+
+        repo.record_error(&task.id, &worker_id, "first failure", 1)
+            .await
+            .unwrap();
+        repo.record_error(&task.id, &worker_id, "second failure", 2)
+            .await
+            .unwrap();
+
+        let errors = repo.get_errors_for_task(&task.id).await.unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].message, "first failure");
+        assert_eq!(errors[0].retry_count, 1);
+        assert_eq!(errors[1].message, "second failure");
+        assert_eq!(errors[1].retry_count, 2);
+    }
+
+    /// A task with no recorded failures returns an empty history
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn no_errors_for_healthy_task(pool: PgPool) {
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgTaskErrorRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core.clone());
+        let task_repo = PgTaskInstanceRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+        let task = task_repo
+            .create_task(task_kind.id, None)
+            .await
+            .unwrap();
+
+        let errors = repo.get_errors_for_task(&task.id).await.unwrap();
+        assert!(errors.is_empty());
+    }
+}
@@ -1,14 +1,87 @@
-use std::time::SystemTime;
+use std::str::FromStr;
+use std::time::Duration;
 
 use async_trait::async_trait;
 use common::{
     models::{TaskInstance, TaskResult},
     TaskKind, TaskStatus,
 };
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
-use crate::repo::{PgRepositoryCore, TaskInstanceRepository};
+use crate::repo::{PgRepositoryCore, RetentionMode, Schedule, TaskInstanceRepository};
+
+/// Backoff strategy used to compute the next `scheduled_at` for a retried
+/// task. `retry_policy_for` always builds the `Exponential` variant from the
+/// task kind's `backoff_base_secs`/`backoff_max_delay_secs`; the mode itself
+/// is not configurable per task kind.
+#[derive(Debug, Clone, Copy)]
+pub enum BackoffMode {
+    /// Always waits the same `delay_secs` before the next attempt.
+    Fixed(i64),
+    /// `delay = base_secs * 2^retries`, capped at `max_delay_secs`
+    Exponential {
+        base_secs: i64,
+        max_delay_secs: i64,
+    },
+}
+
+impl Default for BackoffMode {
+    fn default() -> Self {
+        BackoffMode::Exponential {
+            base_secs: 5,
+            max_delay_secs: 300,
+        }
+    }
+}
+
+impl BackoffMode {
+    /// Computes the delay, in seconds, before the next retry attempt.
+    fn delay_secs(&self, retries: i32) -> i64 {
+        match *self {
+            BackoffMode::Fixed(delay_secs) => delay_secs,
+            BackoffMode::Exponential {
+                base_secs,
+                max_delay_secs,
+            } => (base_secs * 2i64.saturating_pow(retries.max(0) as u32)).min(max_delay_secs),
+        }
+    }
+}
+
+/// How many times a failed task may be retried before it settles as
+/// `Failed`. `tasks.max_retries`/`task_kinds.default_max_retries` store
+/// this as a plain, nullable `i32` column; a negative value means
+/// `Infinite`, matching the sign-based convention already used for "no
+/// override" (`NULL`) on those columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaxRetries {
+    /// Keep retrying until the task succeeds or is cancelled.
+    Infinite,
+    /// Give up and mark the task `Failed` once it has been retried this
+    /// many times.
+    Count(i32),
+}
+
+impl MaxRetries {
+    fn from_db(value: i32) -> Self {
+        if value < 0 {
+            MaxRetries::Infinite
+        } else {
+            MaxRetries::Count(value)
+        }
+    }
+
+    /// Whether a task currently on its `retries`th attempt may be retried
+    /// again.
+    fn allows_retry(&self, retries: i32) -> bool {
+        match self {
+            MaxRetries::Infinite => true,
+            MaxRetries::Count(max) => retries < *max,
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct PgTaskInstanceRepository {
@@ -19,6 +92,235 @@ impl PgTaskInstanceRepository {
     pub fn new(core: PgRepositoryCore) -> Self {
         Self { core }
     }
+
+    /// Looks up the retry policy configured for a task kind: an
+    /// `Exponential` backoff built from its `backoff_base_secs` and
+    /// `backoff_max_delay_secs` (5s and 300s when unset), and the default
+    /// retry ceiling (3 when unset) used when a task doesn't carry its own
+    /// `max_retries`.
+    async fn retry_policy_for(
+        &self,
+        task_kind_id: Uuid,
+        txn: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    ) -> Result<(BackoffMode, i32), sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT backoff_base_secs, backoff_max_delay_secs, default_max_retries
+            FROM task_kinds WHERE id = $1
+            "#,
+            task_kind_id
+        )
+        .fetch_one(&mut **txn)
+        .await?;
+
+        let backoff = BackoffMode::Exponential {
+            base_secs: row.backoff_base_secs.unwrap_or(5),
+            max_delay_secs: row.backoff_max_delay_secs.unwrap_or(300),
+        };
+        Ok((backoff, row.default_max_retries.unwrap_or(3)))
+    }
+
+    /// Computes the next fire time for a cron expression after `after`.
+    /// Falls back to `after` itself if the expression fails to parse.
+    fn next_cron_fire_time(expression: &str, after: OffsetDateTime) -> OffsetDateTime {
+        if let Ok(schedule) = cron::Schedule::from_str(expression) {
+            let after_chrono = chrono::DateTime::from_timestamp(after.unix_timestamp(), 0)
+                .unwrap_or_else(chrono::Utc::now);
+            if let Some(next) = schedule.after(&after_chrono).next() {
+                if let Ok(next) = OffsetDateTime::from_unix_timestamp(next.timestamp()) {
+                    return next;
+                }
+            }
+        }
+        after
+    }
+
+    /// Re-seeds cron chains that went quiet: `upload_task_result` only
+    /// enqueues the next occurrence when an occurrence reaches `Completed`,
+    /// so a chain whose latest occurrence instead ended `Failed`,
+    /// `Cancelled`, `DeadLetter`, `Rejected`, or `Timeout` would otherwise
+    /// never fire again. Scans for `(task_kind_id, cron_pattern)` pairs
+    /// whose most recent occurrence is terminal-but-not-completed and has no
+    /// pending successor already queued, and inserts the next occurrence for
+    /// each. Returns how many chains were reseeded.
+    pub async fn reseed_stalled_cron_tasks(&self) -> Result<u64, sqlx::Error> {
+        let stalled = sqlx::query!(
+            r#"
+            SELECT DISTINCT ON (t.task_kind_id, t.cron_pattern)
+                t.task_kind_id, t.input_data, t.cron_pattern
+            FROM tasks t
+            WHERE t.cron_pattern IS NOT NULL
+              AND t.status IN ('failed', 'cancelled', 'dead_letter', 'rejected', 'timeout')
+            ORDER BY t.task_kind_id, t.cron_pattern, t.created_at DESC
+            "#
+        )
+        .fetch_all(&self.core.reader)
+        .await?;
+
+        let mut reseeded = 0u64;
+        for row in stalled {
+            let cron_pattern = row.cron_pattern.expect("filtered to NOT NULL above");
+
+            let has_successor = sqlx::query!(
+                r#"
+                SELECT 1 AS "exists!" FROM tasks
+                WHERE task_kind_id = $1 AND cron_pattern = $2
+                  AND status NOT IN ('completed', 'failed', 'cancelled', 'dead_letter', 'rejected', 'timeout')
+                LIMIT 1
+                "#,
+                row.task_kind_id,
+                cron_pattern,
+            )
+            .fetch_optional(&self.core.reader)
+            .await?;
+
+            if has_successor.is_some() {
+                continue;
+            }
+
+            let next_fire = Self::next_cron_fire_time(&cron_pattern, OffsetDateTime::now_utc());
+            sqlx::query!(
+                r#"
+                INSERT INTO tasks (id, task_kind_id, input_data, status, assigned_to, scheduled_at, cron_pattern)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                Uuid::new_v4(),
+                row.task_kind_id,
+                row.input_data,
+                "pending",
+                None::<Uuid>,
+                next_fire,
+                cron_pattern,
+            )
+            .execute(&self.core.writer)
+            .await?;
+
+            reseeded += 1;
+        }
+
+        Ok(reseeded)
+    }
+
+    /// Creates a task, deduping against any existing non-terminal task with
+    /// the same `task_kind_id` + `input_data`.
+    ///
+    /// Computes `sha256(task_kind_id || canonical_json(input_data))` and
+    /// relies on a partial unique index over `uniq_hash` for non-terminal
+    /// statuses to make the insert atomic under concurrent callers: if the
+    /// `ON CONFLICT DO NOTHING` doesn't return a row, a duplicate is already
+    /// in flight, so the colliding row is fetched and returned instead.
+    pub async fn create_task_unique(
+        &self,
+        task_kind_id: Uuid,
+        input_data: Option<serde_json::Value>,
+    ) -> Result<TaskInstance, sqlx::Error> {
+        let uniq_hash = Self::uniq_hash(task_kind_id, &input_data);
+        let task_id = Uuid::new_v4();
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO tasks (id, task_kind_id, input_data, status, assigned_to, uniq_hash)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (uniq_hash) WHERE status IN ('pending', 'queued', 'running', 'accepted', 'retrying') DO NOTHING
+            RETURNING id, task_kind_id, input_data, status, assigned_to, created_at
+            "#,
+            task_id,
+            task_kind_id,
+            input_data,
+            "pending",
+            None::<Uuid>,
+            uniq_hash,
+        )
+        .fetch_optional(&self.core.writer)
+        .await?;
+
+        let row = match inserted {
+            Some(row) => row,
+            None => {
+                // A concurrent caller just won the race and inserted the
+                // colliding row; read it back on writer since a replica
+                // behind reader may not have it yet.
+                sqlx::query!(
+                    r#"
+                    SELECT id, task_kind_id, input_data, status, assigned_to, created_at
+                    FROM tasks
+                    WHERE uniq_hash = $1 AND status IN ('pending', 'queued', 'running', 'accepted', 'retrying')
+                    "#,
+                    uniq_hash
+                )
+                .fetch_one(self.core.read_your_writes())
+                .await?
+            }
+        };
+
+        let task_kind_row = sqlx::query!(
+            r#"
+            SELECT id, name FROM task_kinds WHERE id = $1
+            "#,
+            row.task_kind_id
+        )
+        .fetch_one(&self.core.reader)
+        .await?;
+
+        Ok(TaskInstance {
+            id: row.id,
+            task_kind: TaskKind {
+                id: row.task_kind_id,
+                name: task_kind_row.name,
+            },
+            input_data: row.input_data,
+            status: row.status.into(),
+            assigned_to: row.assigned_to,
+            created_at: row.created_at.into(),
+            result: None,
+        })
+    }
+
+    /// Alias for [`create_task_unique`](Self::create_task_unique) under the
+    /// name callers reach for when describing the call as "creating a
+    /// unique task" rather than "creating a task, uniquely".
+    pub async fn create_unique_task(
+        &self,
+        task_kind_id: Uuid,
+        input_data: Option<serde_json::Value>,
+    ) -> Result<TaskInstance, sqlx::Error> {
+        self.create_task_unique(task_kind_id, input_data).await
+    }
+
+    /// Computes `sha256(task_kind_id || canonical_json(input_data))` as a
+    /// hex string, with object keys sorted so the hash is stable regardless
+    /// of the original serde key ordering.
+    fn uniq_hash(task_kind_id: Uuid, input_data: &Option<serde_json::Value>) -> String {
+        let canonical = input_data
+            .as_ref()
+            .map(Self::canonicalize_json)
+            .unwrap_or(serde_json::Value::Null);
+
+        let mut hasher = Sha256::new();
+        hasher.update(task_kind_id.as_bytes());
+        hasher.update(canonical.to_string().as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Recursively sorts object keys so `serde_json::Value::to_string`
+    /// produces a stable, order-independent serialization.
+    fn canonicalize_json(value: &serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::Object(map) => {
+                let mut sorted = serde_json::Map::new();
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort();
+                for key in keys {
+                    sorted.insert(key.clone(), Self::canonicalize_json(&map[key]));
+                }
+                serde_json::Value::Object(sorted)
+            }
+            serde_json::Value::Array(items) => {
+                serde_json::Value::Array(items.iter().map(Self::canonicalize_json).collect())
+            }
+            other => other.clone(),
+        }
+    }
 }
 
 #[async_trait]
@@ -41,7 +343,7 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
             "pending",
             None::<Uuid>,
         )
-        .fetch_one(&self.core.pool)
+        .fetch_one(&self.core.writer)
         .await?;
 
         let task_kind_row = sqlx::query!(
@@ -50,7 +352,7 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
             "#,
             row.task_kind_id
         )
-        .fetch_one(&self.core.pool)
+        .fetch_one(&self.core.reader)
         .await?;
 
         let task = TaskInstance {
@@ -69,6 +371,61 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
         Ok(task)
     }
 
+    async fn create_scheduled_task(
+        &self,
+        task_kind_id: Uuid,
+        input_data: Option<serde_json::Value>,
+        schedule: Schedule,
+    ) -> Result<TaskInstance, sqlx::Error> {
+        let (scheduled_at, cron_pattern) = match &schedule {
+            Schedule::Once(at) => (*at, None),
+            Schedule::Cron(expression) => (
+                Self::next_cron_fire_time(expression, OffsetDateTime::now_utc()),
+                Some(expression.clone()),
+            ),
+        };
+
+        let task_id = Uuid::new_v4();
+        let row = sqlx::query!(
+            r#"
+            INSERT INTO tasks (id, task_kind_id, input_data, status, assigned_to, scheduled_at, cron_pattern)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, task_kind_id, input_data, status, assigned_to, created_at
+            "#,
+            task_id,
+            task_kind_id,
+            input_data,
+            "pending",
+            None::<Uuid>,
+            scheduled_at,
+            cron_pattern,
+        )
+        .fetch_one(&self.core.writer)
+        .await?;
+
+        let task_kind_row = sqlx::query!(
+            r#"
+            SELECT id, name FROM task_kinds WHERE id = $1
+            "#,
+            row.task_kind_id
+        )
+        .fetch_one(&self.core.reader)
+        .await?;
+
+        Ok(TaskInstance {
+            id: row.id,
+            task_kind: TaskKind {
+                id: row.task_kind_id,
+                name: task_kind_row.name,
+            },
+            input_data: row.input_data,
+            status: row.status.into(),
+            assigned_to: row.assigned_to,
+            created_at: row.created_at.into(),
+            result: None,
+        })
+    }
+
     async fn assign_task_to_worker(
         &self,
         task_id: &Uuid,
@@ -76,20 +433,83 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
     ) -> Result<(), sqlx::Error> {
         sqlx::query!(
             r#"
-            UPDATE tasks 
+            UPDATE tasks
             SET assigned_to = $1, status = $2
-            WHERE id = $3
+            WHERE id = $3 AND (scheduled_at IS NULL OR scheduled_at <= NOW())
             "#,
             worker_id,
             String::from(TaskStatus::Queued),
             task_id
         )
-        .execute(&self.core.pool)
+        .execute(&self.core.writer)
         .await?;
 
         Ok(())
     }
 
+    async fn fetch_next_task(
+        &self,
+        worker_id: &Uuid,
+        task_kinds: &[Uuid],
+    ) -> Result<Option<TaskInstance>, sqlx::Error> {
+        let mut txn = self.core.writer.begin().await?;
+
+        let row = sqlx::query!(
+            r#"
+            SELECT id, task_kind_id, input_data, created_at
+            FROM tasks
+            WHERE status = 'pending'
+              AND task_kind_id = ANY($1)
+              AND (scheduled_at IS NULL OR scheduled_at <= NOW())
+            ORDER BY created_at
+            FOR UPDATE SKIP LOCKED
+            LIMIT 1
+            "#,
+            task_kinds
+        )
+        .fetch_optional(&mut *txn)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE tasks SET status = $1, assigned_to = $2 WHERE id = $3
+            "#,
+            String::from(TaskStatus::Queued),
+            worker_id,
+            row.id
+        )
+        .execute(&mut *txn)
+        .await?;
+
+        let task_kind_row = sqlx::query!(
+            r#"
+            SELECT id, name FROM task_kinds WHERE id = $1
+            "#,
+            row.task_kind_id
+        )
+        .fetch_one(&mut *txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(Some(TaskInstance {
+            id: row.id,
+            task_kind: TaskKind {
+                id: task_kind_row.id,
+                name: task_kind_row.name,
+            },
+            input_data: row.input_data,
+            status: TaskStatus::Queued,
+            assigned_to: Some(*worker_id),
+            created_at: row.created_at.into(),
+            result: None,
+        }))
+    }
+
     async fn get_task_by_id(
         &self,
         id: &Uuid,
@@ -97,13 +517,13 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
     ) -> Result<TaskInstance, sqlx::Error> {
         let row = sqlx::query!(
             r#"
-            SELECT id, task_kind_id, input_data, status::text, assigned_to, created_at 
-            FROM tasks 
+            SELECT id, task_kind_id, input_data, status::text, assigned_to, created_at
+            FROM tasks
             WHERE id = $1
             "#,
             id
         )
-        .fetch_one(&self.core.pool)
+        .fetch_one(&self.core.reader)
         .await?;
 
         let task_kind_row = sqlx::query!(
@@ -112,7 +532,7 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
             "#,
             row.task_kind_id
         )
-        .fetch_one(&self.core.pool)
+        .fetch_one(&self.core.reader)
         .await?;
 
         let task_result = if include_result {
@@ -126,7 +546,7 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
                 "#,
                 id
             )
-            .fetch_optional(&self.core.pool)
+            .fetch_optional(&self.core.reader)
             .await?;
 
             row.map(|row| TaskResult {
@@ -168,28 +588,88 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
             String::from(status),
             task_id
         )
-        .execute(&self.core.pool)
+        .execute(&self.core.writer)
         .await?;
 
         Ok(())
     }
 
+    /// Uploads an error result for a task. If the task has retries left
+    /// (`retries < max_retries`), it is rescheduled with a backoff delay
+    /// instead of being marked `Failed`; the error is recorded in
+    /// `task_results` on every attempt so the failure history is preserved.
     async fn upload_task_error(
         &self,
         task_id: &Uuid,
         worker_id: &Uuid,
         error: serde_json::Value,
     ) -> Result<TaskResult, sqlx::Error> {
-        let now = SystemTime::now();
-        let mut txn = self.core.pool.begin().await?;
+        let mut txn = self.core.writer.begin().await?;
 
-        sqlx::query!(
+        let task_row = sqlx::query!(
             r#"
-            UPDATE tasks SET status = $1 WHERE id = $2
+            SELECT retries, max_retries, task_kind_id FROM tasks WHERE id = $1 FOR UPDATE
             "#,
-            String::from(TaskStatus::Failed),
             task_id
         )
+        .fetch_one(&mut *txn)
+        .await?;
+
+        let (backoff, default_max_retries) = self
+            .retry_policy_for(task_row.task_kind_id, &mut txn)
+            .await?;
+        let max_retries =
+            MaxRetries::from_db(task_row.max_retries.unwrap_or(default_max_retries));
+
+        let retry_count = if max_retries.allows_retry(task_row.retries) {
+            let next_retries = task_row.retries + 1;
+            let delay_secs = backoff.delay_secs(next_retries);
+
+            sqlx::query!(
+                r#"
+                UPDATE tasks
+                SET status = $1, retries = $2, scheduled_at = NOW() + ($3 * INTERVAL '1 second'), assigned_to = NULL
+                WHERE id = $4
+                "#,
+                String::from(TaskStatus::Pending),
+                next_retries,
+                delay_secs as f64,
+                task_id
+            )
+            .execute(&mut *txn)
+            .await?;
+
+            next_retries
+        } else {
+            sqlx::query!(
+                r#"
+                UPDATE tasks SET status = $1 WHERE id = $2
+                "#,
+                String::from(TaskStatus::Failed),
+                task_id
+            )
+            .execute(&mut *txn)
+            .await?;
+
+            task_row.retries
+        };
+
+        let error_message = error
+            .get("message")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string());
+
+        sqlx::query!(
+            r#"
+            INSERT INTO task_errors (task_id, worker_id, message, retry_count, occurred_at)
+            VALUES ($1, $2, $3, $4, NOW())
+            "#,
+            task_id,
+            worker_id,
+            error_message,
+            retry_count,
+        )
         .execute(&mut *txn)
         .await?;
 
@@ -225,16 +705,17 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
         worker_id: &Uuid,
         output: serde_json::Value,
     ) -> Result<TaskResult, sqlx::Error> {
-        let mut txn = self.core.pool.begin().await?;
+        let mut txn = self.core.writer.begin().await?;
 
-        sqlx::query!(
+        let completed = sqlx::query!(
             r#"
             UPDATE tasks SET status = $1 WHERE id = $2
+            RETURNING task_kind_id, input_data, cron_pattern
             "#,
             String::from(TaskStatus::Completed),
             task_id
         )
-        .execute(&mut *txn)
+        .fetch_one(&mut *txn)
         .await?;
 
         let result = sqlx::query!(
@@ -252,6 +733,27 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
         .fetch_one(&mut *txn)
         .await?;
 
+        // A cron task enqueues the next occurrence when it completes, so the
+        // schedule keeps advancing without an external scheduler loop.
+        if let Some(cron_pattern) = &completed.cron_pattern {
+            let next_fire = Self::next_cron_fire_time(cron_pattern, OffsetDateTime::now_utc());
+            sqlx::query!(
+                r#"
+                INSERT INTO tasks (id, task_kind_id, input_data, status, assigned_to, scheduled_at, cron_pattern)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                Uuid::new_v4(),
+                completed.task_kind_id,
+                completed.input_data,
+                "pending",
+                None::<Uuid>,
+                next_fire,
+                cron_pattern,
+            )
+            .execute(&mut *txn)
+            .await?;
+        }
+
         txn.commit().await?;
 
         Ok(TaskResult {
@@ -262,6 +764,102 @@ impl TaskInstanceRepository for PgTaskInstanceRepository {
             created_at: result.created_at.into(),
         })
     }
+
+    async fn cleanup_finished_tasks(
+        &self,
+        mode: RetentionMode,
+        older_than: Duration,
+    ) -> Result<u64, sqlx::Error> {
+        let statuses: &[&str] = match mode {
+            RetentionMode::KeepAll => return Ok(0),
+            RetentionMode::RemoveAll => &["completed", "failed", "cancelled", "rejected", "dead_letter"],
+            RetentionMode::RemoveFailed => &["failed"],
+            RetentionMode::RemoveDone => &["completed"],
+        };
+        let cutoff = OffsetDateTime::now_utc() - time::Duration::seconds(older_than.as_secs() as i64);
+
+        // `task_results` rows reference `tasks` via a foreign key with
+        // `ON DELETE CASCADE`, so deleting the task row is enough.
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM tasks
+            WHERE status::text = ANY($1) AND created_at < $2
+            "#,
+            statuses as &[&str],
+            cutoff,
+        )
+        .execute(&self.core.writer)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    // Relies on an index over `worker_heartbeats (worker_id, heartbeat_time)`
+    // to keep the latest-heartbeat lookup a cheap index scan per worker.
+    async fn reclaim_stale_tasks(&self, timeout: Duration) -> Result<Vec<Uuid>, sqlx::Error> {
+        let timeout_secs = timeout.as_secs_f64();
+        let mut txn = self.core.writer.begin().await?;
+
+        let stale = sqlx::query!(
+            r#"
+            SELECT t.id, t.retries, t.max_retries, t.task_kind_id
+            FROM tasks t
+            JOIN workers w ON w.id = t.assigned_to
+            LEFT JOIN LATERAL (
+                SELECT heartbeat_time FROM worker_heartbeats
+                WHERE worker_id = w.id
+                ORDER BY heartbeat_time DESC
+                LIMIT 1
+            ) hb ON true
+            WHERE t.status IN ('queued', 'running')
+              AND COALESCE(hb.heartbeat_time, w.registered_at) < NOW() - ($1 * INTERVAL '1 second')
+            FOR UPDATE OF t SKIP LOCKED
+            "#,
+            timeout_secs
+        )
+        .fetch_all(&mut *txn)
+        .await?;
+
+        let mut reclaimed = Vec::with_capacity(stale.len());
+
+        for task in stale {
+            let (_, default_max_retries) =
+                self.retry_policy_for(task.task_kind_id, &mut txn).await?;
+            let max_retries =
+                MaxRetries::from_db(task.max_retries.unwrap_or(default_max_retries));
+
+            if max_retries.allows_retry(task.retries) {
+                sqlx::query!(
+                    r#"
+                    UPDATE tasks
+                    SET status = $1, retries = $2, assigned_to = NULL
+                    WHERE id = $3
+                    "#,
+                    String::from(TaskStatus::Pending),
+                    task.retries + 1,
+                    task.id
+                )
+                .execute(&mut *txn)
+                .await?;
+            } else {
+                sqlx::query!(
+                    r#"
+                    UPDATE tasks SET status = $1, assigned_to = NULL WHERE id = $2
+                    "#,
+                    String::from(TaskStatus::Failed),
+                    task.id
+                )
+                .execute(&mut *txn)
+                .await?;
+            }
+
+            reclaimed.push(task.id);
+        }
+
+        txn.commit().await?;
+
+        Ok(reclaimed)
+    }
 }
 
 #[cfg(test)]
@@ -330,6 +928,7 @@ mod tests {
                 worker_id,
                 "Test Worker".to_string(),
                 vec![task_kind.clone()],
+                None,
             )
             .await
             .unwrap();
@@ -469,6 +1068,7 @@ mod tests {
                 worker_id,
                 "Test Worker".to_string(),
                 vec![task_kind.clone()],
+                None,
             )
             .await
             .unwrap();
@@ -480,4 +1080,177 @@ mod tests {
         assert_eq!(updated.assigned_to, Some(worker_id));
         assert_eq!(updated.status, TaskStatus::Queued);
     }
+
+    /// A task scheduled for the future isn't eligible for assignment until
+    /// its `scheduled_at` time has passed.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn scheduled_task_not_assignable_until_due(pool: PgPool) {
+        use crate::repo::Schedule;
+        use time::OffsetDateTime;
+
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgTaskInstanceRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core.clone());
+        let worker_repo = PgWorkerRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test Task".to_string())
+            .await
+            .unwrap();
+        let worker_id = Uuid::new_v4();
+        worker_repo
+            .register_worker(
+                worker_id,
+                "Test Worker".to_string(),
+                vec![task_kind.clone()],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let future = OffsetDateTime::now_utc() + time::Duration::minutes(10);
+        let task = repo
+            .create_scheduled_task(task_kind.id, None, Schedule::Once(future))
+            .await
+            .unwrap();
+
+        repo.assign_task_to_worker(&task.id, &worker_id)
+            .await
+            .unwrap();
+        let unchanged = repo.get_task_by_id(&task.id, false).await.unwrap();
+        assert_eq!(unchanged.status, TaskStatus::Pending);
+        assert!(unchanged.assigned_to.is_none());
+
+        let past = OffsetDateTime::now_utc() - time::Duration::minutes(10);
+        let due_task = repo
+            .create_scheduled_task(task_kind.id, None, Schedule::Once(past))
+            .await
+            .unwrap();
+
+        repo.assign_task_to_worker(&due_task.id, &worker_id)
+            .await
+            .unwrap();
+        let assigned = repo.get_task_by_id(&due_task.id, false).await.unwrap();
+        assert_eq!(assigned.status, TaskStatus::Queued);
+        assert_eq!(assigned.assigned_to, Some(worker_id));
+    }
+
+    /// `upload_task_error` should reschedule a task with retries left
+    /// instead of failing it, and only settle it as `Failed` once
+    /// `max_retries` is exhausted.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn upload_task_error_retries_then_fails(pool: PgPool) {
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgTaskInstanceRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test Task".to_string())
+            .await
+            .unwrap();
+        let task = repo.create_task(task_kind.id, None).await.unwrap();
+        let worker_id = Uuid::new_v4();
+        let error = serde_json::json!({"error": "boom"});
+
+        // Default max_retries is 3 (see `retry_policy_for`), so the first
+        // three errors should reschedule the task instead of failing it.
+        for _ in 0..3 {
+            repo.upload_task_error(&task.id, &worker_id, error.clone())
+                .await
+                .unwrap();
+            let retried = repo.get_task_by_id(&task.id, false).await.unwrap();
+            assert_eq!(retried.status, TaskStatus::Pending);
+            assert!(retried.assigned_to.is_none());
+        }
+
+        // The fourth error exhausts retries and settles the task as Failed.
+        repo.upload_task_error(&task.id, &worker_id, error)
+            .await
+            .unwrap();
+        let failed = repo.get_task_by_id(&task.id, false).await.unwrap();
+        assert_eq!(failed.status, TaskStatus::Failed);
+    }
+
+    /// Completing a cron-scheduled task should enqueue its next occurrence
+    /// rather than leave the schedule to stop after one run.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn completing_cron_task_enqueues_next_occurrence(pool: PgPool) {
+        use crate::repo::Schedule;
+
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgTaskInstanceRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test Task".to_string())
+            .await
+            .unwrap();
+        let worker_id = Uuid::new_v4();
+
+        // Fires every minute, so the next occurrence is always in the future
+        // relative to "now".
+        let task = repo
+            .create_scheduled_task(
+                task_kind.id,
+                None,
+                Schedule::Cron("0 * * * * *".to_string()),
+            )
+            .await
+            .unwrap();
+
+        repo.upload_task_result(&task.id, &worker_id, serde_json::json!({"ok": true}))
+            .await
+            .unwrap();
+
+        let follow_up_count = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "count!" FROM tasks
+            WHERE task_kind_id = $1 AND id != $2 AND cron_pattern IS NOT NULL
+            "#,
+            task_kind.id,
+            task.id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .count;
+
+        assert_eq!(follow_up_count, 1);
+    }
+
+    /// Calling `create_task_unique` twice with the same task kind and input
+    /// data should return the same task instead of creating a duplicate.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn create_task_unique_dedupes_pending_task(pool: PgPool) {
+        let repo = PgTaskInstanceRepository::new(PgRepositoryCore::new(pool.clone()));
+        let task_kind_repo = PgTaskKindRepository::new(PgRepositoryCore::new(pool));
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test Task".to_string())
+            .await
+            .unwrap();
+
+        // Same keys, different insertion order, to exercise canonicalization.
+        let input_a = serde_json::json!({"a": 1, "b": 2});
+        let input_b = serde_json::json!({"b": 2, "a": 1});
+
+        let first = repo
+            .create_task_unique(task_kind.id, Some(input_a))
+            .await
+            .unwrap();
+        let second = repo
+            .create_task_unique(task_kind.id, Some(input_b))
+            .await
+            .unwrap();
+
+        assert_eq!(first.id, second.id);
+
+        // Different input data should not be deduped.
+        let distinct_input = serde_json::json!({"a": 1, "b": 3});
+        let third = repo
+            .create_task_unique(task_kind.id, Some(distinct_input))
+            .await
+            .unwrap();
+        assert_ne!(first.id, third.id);
+    }
 }
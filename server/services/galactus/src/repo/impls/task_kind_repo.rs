@@ -30,7 +30,7 @@ impl TaskKindRepository for PgTaskKindRepository {
             Uuid::new_v4(),
             name,
         )
-        .fetch_one(&self.core.pool)
+        .fetch_one(&self.core.writer)
         .await?;
 
         Ok(TaskKind {
@@ -45,7 +45,7 @@ impl TaskKindRepository for PgTaskKindRepository {
             SELECT id, name FROM task_kinds
             "#
         )
-        .fetch_all(&self.core.pool)
+        .fetch_all(&self.core.reader)
         .await?;
 
         Ok(rows
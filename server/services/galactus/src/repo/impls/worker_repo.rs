@@ -1,6 +1,7 @@
 use async_trait::async_trait;
-use common::models::{TaskKind, Worker};
-use std::time::SystemTime;
+use common::models::{TaskKind, Worker, WorkerState, DEFAULT_MAX_CONCURRENT_TASKS};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, SystemTime};
 use uuid::Uuid;
 
 use crate::repo::{PgRepositoryCore, WorkerRepository};
@@ -14,6 +15,160 @@ impl PgWorkerRepository {
     pub fn new(core: PgRepositoryCore) -> Self {
         Self { core }
     }
+
+    /// Computes `sha256(token)` as a hex string. Only the hash is ever
+    /// persisted, so a leaked `workers` row doesn't hand out usable tokens.
+    fn hash_token(token: &str) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(token.as_bytes());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Marks workers whose most recent heartbeat (or `registered_at`, if
+    /// they've never sent one) is older than `ttl` as inactive, and
+    /// re-queues any in-flight tasks still assigned to them (`Running`,
+    /// `Accepted`, or `Queued` — any status between dispatch and a terminal
+    /// outcome). Tasks with retries left (`retries < max_retries`, falling
+    /// back to the task kind's `default_max_retries`, or any negative
+    /// ceiling, which means unlimited retries) go back to `Pending`
+    /// with their retry counter bumped; a task that has already exhausted
+    /// its retries settles as `Failed` instead of bouncing between workers
+    /// forever. This is the crash-recovery counterpart to the `FOR UPDATE
+    /// SKIP LOCKED` claim flow: without it, a worker that dies mid-task
+    /// leaves that task stuck forever.
+    ///
+    /// Returns the ids of the workers that were reaped.
+    pub async fn reap_stale_workers(&self, ttl: Duration) -> Result<Vec<Uuid>, sqlx::Error> {
+        let ttl_secs = ttl.as_secs_f64();
+        let mut txn = self.core.writer.begin().await?;
+
+        let stale = sqlx::query!(
+            r#"
+            SELECT w.id
+            FROM workers w
+            LEFT JOIN LATERAL (
+                SELECT heartbeat_time FROM worker_heartbeats
+                WHERE worker_id = w.id
+                ORDER BY heartbeat_time DESC
+                LIMIT 1
+            ) hb ON true
+            WHERE w.state = 'active'
+              AND COALESCE(hb.heartbeat_time, w.registered_at) < NOW() - ($1 * INTERVAL '1 second')
+            "#,
+            ttl_secs
+        )
+        .fetch_all(&mut *txn)
+        .await?;
+
+        let stale_ids: Vec<Uuid> = stale.into_iter().map(|row| row.id).collect();
+
+        if stale_ids.is_empty() {
+            txn.commit().await?;
+            return Ok(stale_ids);
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE workers SET state = 'offline' WHERE id = ANY($1)
+            "#,
+            &stale_ids
+        )
+        .execute(&mut *txn)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE tasks t
+            SET status = CASE
+                    WHEN COALESCE(t.max_retries, tk.default_max_retries, 3) < 0
+                      OR t.retries < COALESCE(t.max_retries, tk.default_max_retries, 3)
+                    THEN 'pending'
+                    ELSE 'failed'
+                END,
+                retries = CASE
+                    WHEN COALESCE(t.max_retries, tk.default_max_retries, 3) < 0
+                      OR t.retries < COALESCE(t.max_retries, tk.default_max_retries, 3)
+                    THEN t.retries + 1
+                    ELSE t.retries
+                END,
+                assigned_to = NULL
+            FROM task_kinds tk
+            WHERE t.task_kind_id = tk.id
+              AND t.assigned_to = ANY($1)
+              AND t.status IN ('running', 'accepted', 'queued')
+            "#,
+            &stale_ids
+        )
+        .execute(&mut *txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(stale_ids)
+    }
+
+    /// Returns every `active` worker whose latest heartbeat (or
+    /// `registered_at`, if it's never sent one) is older than `NOW() -
+    /// threshold`, without acting on them. Unlike [`Self::reap_stale_workers`]
+    /// this doesn't flip anyone inactive - it's the read-only counterpart a
+    /// supervisor can poll to decide whether reaping is warranted, or that
+    /// monitoring can use to alert before a worker is actually reaped. Uses
+    /// the same `LEFT JOIN LATERAL` as `reap_stale_workers` so the staleness
+    /// check is one round trip and the threshold comparison happens in SQL,
+    /// avoiding clock skew between app and DB.
+    pub async fn find_stale_workers(&self, threshold: Duration) -> Result<Vec<Worker>, sqlx::Error> {
+        let threshold_secs = threshold.as_secs_f64();
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT w.id, w.name, w.registered_at, w.state, w.max_concurrent_tasks
+            FROM workers w
+            LEFT JOIN LATERAL (
+                SELECT heartbeat_time FROM worker_heartbeats
+                WHERE worker_id = w.id
+                ORDER BY heartbeat_time DESC
+                LIMIT 1
+            ) hb ON true
+            WHERE w.state = 'active'
+              AND COALESCE(hb.heartbeat_time, w.registered_at) < NOW() - ($1 * INTERVAL '1 second')
+            "#,
+            threshold_secs
+        )
+        .fetch_all(&self.core.reader)
+        .await?;
+
+        let mut workers = Vec::with_capacity(rows.len());
+        for row in rows {
+            let task_kinds = sqlx::query!(
+                r#"
+                SELECT tt.id, tt.name
+                FROM task_kinds tt
+                JOIN worker_task_kinds wtt ON wtt.task_kind_id = tt.id
+                WHERE wtt.worker_id = $1
+                "#,
+                row.id
+            )
+            .fetch_all(&self.core.reader)
+            .await?;
+
+            workers.push(Worker {
+                id: row.id,
+                name: row.name,
+                registered_at: row.registered_at.into(),
+                task_kind: task_kinds
+                    .into_iter()
+                    .map(|tt| TaskKind {
+                        id: tt.id,
+                        name: tt.name,
+                    })
+                    .collect(),
+                state: row.state.into(),
+                max_concurrent_tasks: row.max_concurrent_tasks,
+            });
+        }
+
+        Ok(workers)
+    }
 }
 
 #[async_trait]
@@ -23,17 +178,20 @@ impl WorkerRepository for PgWorkerRepository {
         id: Uuid,
         name: String,
         task_kinds: Vec<TaskKind>,
+        max_concurrent_tasks: Option<i32>,
     ) -> Result<Worker, sqlx::Error> {
-        let mut txn = self.core.pool.begin().await?;
+        let max_concurrent_tasks = max_concurrent_tasks.unwrap_or(DEFAULT_MAX_CONCURRENT_TASKS);
+        let mut txn = self.core.writer.begin().await?;
 
         sqlx::query!(
             r#"
-            INSERT INTO workers (id, name, registered_at)
-            VALUES ($1, $2, NOW())
-            ON CONFLICT (id) DO UPDATE SET name = $2
+            INSERT INTO workers (id, name, registered_at, max_concurrent_tasks)
+            VALUES ($1, $2, NOW(), $3)
+            ON CONFLICT (id) DO UPDATE SET name = $2, max_concurrent_tasks = $3
             "#,
             id,
             name,
+            max_concurrent_tasks,
         )
         .execute(&mut *txn)
         .await?;
@@ -66,11 +224,13 @@ impl WorkerRepository for PgWorkerRepository {
 
         let row = sqlx::query!(
             r#"
-            SELECT registered_at, active FROM workers WHERE id = $1
+            SELECT registered_at, state FROM workers WHERE id = $1
             "#,
             id
         )
-        .fetch_one(&self.core.pool)
+        // Read the row back on writer, not reader: it was just inserted in
+        // this same call, and a replica behind reader may not have it yet.
+        .fetch_one(self.core.read_your_writes())
         .await?;
 
         Ok(Worker {
@@ -78,83 +238,83 @@ impl WorkerRepository for PgWorkerRepository {
             name,
             registered_at: row.registered_at.into(),
             task_kind: task_kinds.clone(),
-            active: row.active,
+            state: row.state.into(),
+            max_concurrent_tasks,
         })
     }
 
     async fn get_worker_by_id(&self, id: &Uuid) -> Result<Worker, sqlx::Error> {
-        let worker = sqlx::query!(
+        let row = sqlx::query!(
             r#"
-            SELECT name, registered_at, active FROM workers WHERE id = $1
+            SELECT
+                w.name,
+                w.registered_at,
+                w.state,
+                w.max_concurrent_tasks,
+                COALESCE(
+                    json_agg(json_build_object('id', tt.id, 'name', tt.name))
+                        FILTER (WHERE tt.id IS NOT NULL),
+                    '[]'
+                ) AS "task_kinds!: serde_json::Value"
+            FROM workers w
+            LEFT JOIN worker_task_kinds wtt ON wtt.worker_id = w.id
+            LEFT JOIN task_kinds tt ON tt.id = wtt.task_kind_id
+            WHERE w.id = $1
+            GROUP BY w.id, w.name, w.registered_at, w.state
             "#,
             id
         )
-        .fetch_one(&self.core.pool)
+        .fetch_one(&self.core.reader)
         .await?;
 
-        let task_kinds = sqlx::query!(
-            r#"
-            SELECT tt.id, tt.name 
-            FROM task_kinds tt
-            JOIN worker_task_kinds wtt ON wtt.task_kind_id = tt.id
-            WHERE wtt.worker_id = $1
-            "#,
-            id
-        )
-        .fetch_all(&self.core.pool)
-        .await?;
+        let task_kind: Vec<TaskKind> =
+            serde_json::from_value(row.task_kinds).unwrap_or_default();
 
         Ok(Worker {
             id: *id,
-            name: worker.name,
-            registered_at: worker.registered_at.into(),
-            task_kind: task_kinds
-                .into_iter()
-                .map(|tt| TaskKind {
-                    id: tt.id,
-                    name: tt.name,
-                })
-                .collect(),
-            active: worker.active,
+            name: row.name,
+            registered_at: row.registered_at.into(),
+            task_kind,
+            state: row.state.into(),
+            max_concurrent_tasks: row.max_concurrent_tasks,
         })
     }
 
     async fn get_all_workers(&self) -> Result<Vec<Worker>, sqlx::Error> {
-        let workers = sqlx::query!(
+        let rows = sqlx::query!(
             r#"
-            SELECT id, name, registered_at, active FROM workers
+            SELECT
+                w.id,
+                w.name,
+                w.registered_at,
+                w.state,
+                w.max_concurrent_tasks,
+                COALESCE(
+                    json_agg(json_build_object('id', tt.id, 'name', tt.name))
+                        FILTER (WHERE tt.id IS NOT NULL),
+                    '[]'
+                ) AS "task_kinds!: serde_json::Value"
+            FROM workers w
+            LEFT JOIN worker_task_kinds wtt ON wtt.worker_id = w.id
+            LEFT JOIN task_kinds tt ON tt.id = wtt.task_kind_id
+            GROUP BY w.id, w.name, w.registered_at, w.state
             "#
         )
-        .fetch_all(&self.core.pool)
+        .fetch_all(&self.core.reader)
         .await?;
 
-        let mut result = Vec::new();
-
-        for worker in workers {
-            let task_kinds = sqlx::query!(
-                r#"
-                SELECT tt.id, tt.name 
-                FROM task_kinds tt
-                JOIN worker_task_kinds wtt ON wtt.task_kind_id = tt.id
-                WHERE wtt.worker_id = $1
-                "#,
-                worker.id
-            )
-            .fetch_all(&self.core.pool)
-            .await?;
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let task_kind: Vec<TaskKind> =
+                serde_json::from_value(row.task_kinds).unwrap_or_default();
 
             result.push(Worker {
-                id: worker.id,
-                name: worker.name,
-                registered_at: worker.registered_at.into(),
-                task_kind: task_kinds
-                    .into_iter()
-                    .map(|tt| TaskKind {
-                        id: tt.id,
-                        name: tt.name,
-                    })
-                    .collect(),
-                active: worker.active,
+                id: row.id,
+                name: row.name,
+                registered_at: row.registered_at.into(),
+                task_kind,
+                state: row.state.into(),
+                max_concurrent_tasks: row.max_concurrent_tasks,
             });
         }
 
@@ -162,28 +322,89 @@ impl WorkerRepository for PgWorkerRepository {
     }
 
     async fn set_worker_active(&self, worker_id: &Uuid, active: bool) -> Result<(), sqlx::Error> {
+        let state = if active {
+            WorkerState::Active
+        } else {
+            WorkerState::Offline
+        };
+        self.set_worker_state(worker_id, state).await
+    }
+
+    async fn set_worker_state(
+        &self,
+        worker_id: &Uuid,
+        state: WorkerState,
+    ) -> Result<(), sqlx::Error> {
         sqlx::query!(
             r#"
-            UPDATE workers SET active = $1 WHERE id = $2
+            UPDATE workers SET state = $1 WHERE id = $2
             "#,
-            active,
+            String::from(state),
             worker_id
         )
-        .execute(&self.core.pool)
+        .execute(&self.core.writer)
         .await?;
 
         Ok(())
     }
 
+    async fn get_workers_by_state(&self, state: WorkerState) -> Result<Vec<Worker>, sqlx::Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT
+                w.id,
+                w.name,
+                w.registered_at,
+                w.state,
+                w.max_concurrent_tasks,
+                COALESCE(
+                    json_agg(json_build_object('id', tt.id, 'name', tt.name))
+                        FILTER (WHERE tt.id IS NOT NULL),
+                    '[]'
+                ) AS "task_kinds!: serde_json::Value"
+            FROM workers w
+            LEFT JOIN worker_task_kinds wtt ON wtt.worker_id = w.id
+            LEFT JOIN task_kinds tt ON tt.id = wtt.task_kind_id
+            WHERE w.state = $1
+            GROUP BY w.id, w.name, w.registered_at, w.state
+            "#,
+            String::from(state)
+        )
+        .fetch_all(&self.core.reader)
+        .await?;
+
+        let mut result = Vec::with_capacity(rows.len());
+        for row in rows {
+            let task_kind: Vec<TaskKind> =
+                serde_json::from_value(row.task_kinds).unwrap_or_default();
+
+            result.push(Worker {
+                id: row.id,
+                name: row.name,
+                registered_at: row.registered_at.into(),
+                task_kind,
+                state: row.state.into(),
+                max_concurrent_tasks: row.max_concurrent_tasks,
+            });
+        }
+
+        Ok(result)
+    }
+
     async fn record_heartbeat(&self, worker_id: &Uuid) -> Result<(), sqlx::Error> {
+        // Upserted on `worker_id` (requires a unique constraint on that
+        // column) rather than appended, so a worker heartbeating every few
+        // seconds doesn't leave one row behind per beat forever - there's
+        // only ever one heartbeat row per worker, just kept fresh in place.
         sqlx::query!(
             r#"
             INSERT INTO worker_heartbeats (worker_id, heartbeat_time)
             VALUES ($1, NOW())
+            ON CONFLICT (worker_id) DO UPDATE SET heartbeat_time = NOW()
             "#,
             worker_id
         )
-        .execute(&self.core.pool)
+        .execute(&self.core.writer)
         .await?;
 
         Ok(())
@@ -192,19 +413,150 @@ impl WorkerRepository for PgWorkerRepository {
     async fn get_latest_heartbeat(&self, worker_id: &Uuid) -> Result<SystemTime, sqlx::Error> {
         let row = sqlx::query!(
             r#"
-            SELECT heartbeat_time 
-            FROM worker_heartbeats 
-            WHERE worker_id = $1 
-            ORDER BY heartbeat_time DESC 
+            SELECT heartbeat_time
+            FROM worker_heartbeats
+            WHERE worker_id = $1
+            ORDER BY heartbeat_time DESC
             LIMIT 1
             "#,
             worker_id
         )
-        .fetch_one(&self.core.pool)
+        .fetch_one(&self.core.reader)
         .await?;
 
         Ok(row.heartbeat_time.into())
     }
+
+    /// Deletes heartbeat rows older than `cutoff`. With `record_heartbeat`
+    /// upserting in place, this removes the row of every worker whose last
+    /// heartbeat predates the cutoff, whether or not the reaper has since
+    /// marked it offline (reaping leaves heartbeat rows in place). Returns
+    /// the number of rows deleted.
+    async fn prune_heartbeats_older_than(&self, cutoff: Duration) -> Result<u64, sqlx::Error> {
+        let cutoff_secs = cutoff.as_secs_f64();
+
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM worker_heartbeats
+            WHERE heartbeat_time < NOW() - ($1 * INTERVAL '1 second')
+            "#,
+            cutoff_secs
+        )
+        .execute(&self.core.writer)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn find_available_worker_for_kind(
+        &self,
+        task_kind_id: Uuid,
+        heartbeat_threshold: Duration,
+    ) -> Result<Option<Worker>, sqlx::Error> {
+        let threshold_secs = heartbeat_threshold.as_secs_f64();
+        let mut txn = self.core.writer.begin().await?;
+
+        let row = sqlx::query!(
+            r#"
+            SELECT w.id, w.name, w.registered_at, w.state, w.max_concurrent_tasks
+            FROM workers w
+            JOIN worker_task_kinds wtt ON wtt.worker_id = w.id AND wtt.task_kind_id = $1
+            JOIN LATERAL (
+                SELECT heartbeat_time FROM worker_heartbeats
+                WHERE worker_id = w.id
+                ORDER BY heartbeat_time DESC
+                LIMIT 1
+            ) hb ON true
+            LEFT JOIN LATERAL (
+                SELECT COUNT(*) AS running_count FROM tasks t
+                WHERE t.assigned_to = w.id
+                  AND t.status IN ('running', 'accepted', 'queued')
+            ) running ON true
+            WHERE w.state = 'active'
+              AND hb.heartbeat_time > NOW() - ($2 * INTERVAL '1 second')
+              AND COALESCE(running.running_count, 0) < w.max_concurrent_tasks
+            ORDER BY COALESCE(running.running_count, 0) ASC
+            LIMIT 1
+            FOR UPDATE OF w SKIP LOCKED
+            "#,
+            task_kind_id,
+            threshold_secs
+        )
+        .fetch_optional(&mut *txn)
+        .await?;
+
+        let Some(row) = row else {
+            txn.commit().await?;
+            return Ok(None);
+        };
+
+        let task_kinds = sqlx::query!(
+            r#"
+            SELECT tt.id, tt.name
+            FROM task_kinds tt
+            JOIN worker_task_kinds wtt ON wtt.task_kind_id = tt.id
+            WHERE wtt.worker_id = $1
+            "#,
+            row.id
+        )
+        .fetch_all(&mut *txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(Some(Worker {
+            id: row.id,
+            name: row.name,
+            registered_at: row.registered_at.into(),
+            task_kind: task_kinds
+                .into_iter()
+                .map(|tt| TaskKind {
+                    id: tt.id,
+                    name: tt.name,
+                })
+                .collect(),
+            state: row.state.into(),
+            max_concurrent_tasks: row.max_concurrent_tasks,
+        }))
+    }
+
+    async fn issue_worker_token(&self, worker_id: &Uuid) -> Result<String, sqlx::Error> {
+        let token = format!(
+            "{}{}",
+            Uuid::new_v4().simple(),
+            Uuid::new_v4().simple()
+        );
+        let token_hash = Self::hash_token(&token);
+
+        sqlx::query!(
+            r#"
+            UPDATE workers SET token_hash = $1 WHERE id = $2
+            "#,
+            token_hash,
+            worker_id
+        )
+        .execute(&self.core.writer)
+        .await?;
+
+        Ok(token)
+    }
+
+    async fn verify_worker_token(
+        &self,
+        worker_id: &Uuid,
+        token: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT token_hash FROM workers WHERE id = $1
+            "#,
+            worker_id
+        )
+        .fetch_one(&self.core.reader)
+        .await?;
+
+        Ok(row.token_hash.as_deref() == Some(Self::hash_token(token).as_str()))
+    }
 }
 
 #[cfg(test)]
@@ -239,6 +591,7 @@ mod tests {
                 worker_id,
                 "Test Worker".to_string(),
                 vec![task_kind.clone()],
+                None,
             )
             .await
             .unwrap();
@@ -247,7 +600,7 @@ mod tests {
         assert_eq!(worker.name, "Test Worker");
         assert_eq!(worker.task_kind.len(), 1);
         assert_eq!(worker.task_kind[0].id, task_kind.id);
-        assert!(worker.active);
+        assert_eq!(worker.state, WorkerState::Active);
 
         let retrieved = repo.get_worker_by_id(&worker_id).await.unwrap();
         assert_eq!(worker.id, retrieved.id);
@@ -271,6 +624,7 @@ mod tests {
                 Uuid::new_v4(),
                 "Worker 1".to_string(),
                 vec![task_kind.clone()],
+                None,
             )
             .await
             .unwrap();
@@ -280,6 +634,7 @@ mod tests {
                 Uuid::new_v4(),
                 "Worker 2".to_string(),
                 vec![task_kind.clone()],
+                None,
             )
             .await
             .unwrap();
@@ -290,6 +645,57 @@ mod tests {
         assert!(all_workers.iter().any(|w| w.id == worker2.id));
     }
 
+    /// Registers a fleet of workers, each with several task kinds, and
+    /// checks that `get_all_workers`/`get_worker_by_id` still fold in the
+    /// correct task kinds for every worker now that they're fetched via a
+    /// single aggregated join instead of one `worker_task_kinds` query per
+    /// worker.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn get_all_workers_with_multiple_task_kinds_each(pool: PgPool) {
+        let repo = PgWorkerRepository::new(PgRepositoryCore::new(pool.clone()));
+        let task_kind_repo = PgTaskKindRepository::new(PgRepositoryCore::new(pool));
+
+        let kind_a = task_kind_repo
+            .get_or_create_task_kind("Kind A".to_string())
+            .await
+            .unwrap();
+        let kind_b = task_kind_repo
+            .get_or_create_task_kind("Kind B".to_string())
+            .await
+            .unwrap();
+        let kind_c = task_kind_repo
+            .get_or_create_task_kind("Kind C".to_string())
+            .await
+            .unwrap();
+
+        let mut workers = Vec::new();
+        for i in 0..5 {
+            let worker = repo
+                .register_worker(
+                    Uuid::new_v4(),
+                    format!("Worker {i}"),
+                    vec![kind_a.clone(), kind_b.clone(), kind_c.clone()],
+                    None,
+                )
+                .await
+                .unwrap();
+            workers.push(worker);
+        }
+
+        let all_workers = repo.get_all_workers().await.unwrap();
+        assert_eq!(all_workers.len(), 5);
+        for worker in &workers {
+            let fetched = all_workers.iter().find(|w| w.id == worker.id).unwrap();
+            assert_eq!(fetched.task_kind.len(), 3);
+            assert!(fetched.task_kind.iter().any(|tk| tk.id == kind_a.id));
+            assert!(fetched.task_kind.iter().any(|tk| tk.id == kind_b.id));
+            assert!(fetched.task_kind.iter().any(|tk| tk.id == kind_c.id));
+
+            let by_id = repo.get_worker_by_id(&worker.id).await.unwrap();
+            assert_eq!(by_id.task_kind.len(), 3);
+        }
+    }
+
     /// Registers a worker and then updates its name and task kinds
     #[sqlx::test(migrator = "db_common::MIGRATOR")]
     async fn update_worker(pool: PgPool) {
@@ -307,7 +713,7 @@ mod tests {
 
         let worker_id = Uuid::new_v4();
         let worker = repo
-            .register_worker(worker_id, "Original Name".to_string(), vec![task_kind1])
+            .register_worker(worker_id, "Original Name".to_string(), vec![task_kind1], None)
             .await
             .unwrap();
 
@@ -317,6 +723,7 @@ mod tests {
                 worker_id,
                 "Updated Name".to_string(),
                 vec![task_kind2.clone()],
+                None,
             )
             .await
             .unwrap();
@@ -339,14 +746,14 @@ mod tests {
             .unwrap();
 
         let worker = repo
-            .register_worker(Uuid::new_v4(), "Test Worker".to_string(), vec![task_kind])
+            .register_worker(Uuid::new_v4(), "Test Worker".to_string(), vec![task_kind], None)
             .await
             .unwrap();
-        assert!(worker.active);
+        assert_eq!(worker.state, WorkerState::Active);
 
         repo.set_worker_active(&worker.id, false).await.unwrap();
         let updated = repo.get_worker_by_id(&worker.id).await.unwrap();
-        assert!(!updated.active);
+        assert_eq!(updated.state, WorkerState::Offline);
     }
 
     /// Registers a worker and then records a heartbeat
@@ -361,7 +768,7 @@ mod tests {
             .unwrap();
 
         let worker = repo
-            .register_worker(Uuid::new_v4(), "Test Worker".to_string(), vec![task_kind])
+            .register_worker(Uuid::new_v4(), "Test Worker".to_string(), vec![task_kind], None)
             .await
             .unwrap();
 
@@ -373,6 +780,84 @@ mod tests {
         assert!(now.duration_since(heartbeat).unwrap().as_secs() < 1);
     }
 
+    /// Recording a second heartbeat for the same worker overwrites the
+    /// first in place rather than appending a new row.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn repeated_heartbeats_upsert_in_place(pool: PgPool) {
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgWorkerRepository::new(core);
+        let task_kind_repo = PgTaskKindRepository::new(PgRepositoryCore::new(pool.clone()));
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+
+        let worker = repo
+            .register_worker(Uuid::new_v4(), "Test Worker".to_string(), vec![task_kind], None)
+            .await
+            .unwrap();
+
+        repo.record_heartbeat(&worker.id).await.unwrap();
+        repo.record_heartbeat(&worker.id).await.unwrap();
+        repo.record_heartbeat(&worker.id).await.unwrap();
+
+        let count = sqlx::query!(
+            "SELECT COUNT(*) AS count FROM worker_heartbeats WHERE worker_id = $1",
+            worker.id
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+        .count
+        .unwrap_or(0);
+
+        assert_eq!(count, 1);
+    }
+
+    /// `prune_heartbeats_older_than` removes only rows whose heartbeat
+    /// predates the cutoff, leaving fresh ones untouched.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn prune_heartbeats_removes_only_stale_rows(pool: PgPool) {
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgWorkerRepository::new(core);
+        let task_kind_repo = PgTaskKindRepository::new(PgRepositoryCore::new(pool.clone()));
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+
+        let stale_worker = repo
+            .register_worker(Uuid::new_v4(), "Stale Worker".to_string(), vec![task_kind.clone()], None)
+            .await
+            .unwrap();
+        let fresh_worker = repo
+            .register_worker(Uuid::new_v4(), "Fresh Worker".to_string(), vec![task_kind], None)
+            .await
+            .unwrap();
+
+        repo.record_heartbeat(&stale_worker.id).await.unwrap();
+        repo.record_heartbeat(&fresh_worker.id).await.unwrap();
+
+        sqlx::query!(
+            "UPDATE worker_heartbeats SET heartbeat_time = NOW() - INTERVAL '1 hour' WHERE worker_id = $1",
+            stale_worker.id
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let pruned = repo
+            .prune_heartbeats_older_than(Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(pruned, 1);
+
+        assert!(repo.get_latest_heartbeat(&stale_worker.id).await.is_err());
+        assert!(repo.get_latest_heartbeat(&fresh_worker.id).await.is_ok());
+    }
+
     /// Attempts to retrieve a nonexistent worker by id (should fail)
     #[sqlx::test(migrator = "db_common::MIGRATOR")]
     async fn get_nonexistent_worker(pool: PgPool) {
@@ -388,4 +873,275 @@ mod tests {
         let result = repo.get_latest_heartbeat(&Uuid::new_v4()).await;
         assert!(result.is_err());
     }
+
+    /// A worker whose registration predates the TTL and has never sent a
+    /// heartbeat is reaped: it's marked inactive and its running task is
+    /// re-queued to pending.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn reap_stale_workers_requeues_running_task(pool: PgPool) {
+        use crate::repo::{PgTaskInstanceRepository, TaskInstanceRepository};
+
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgWorkerRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core.clone());
+        let task_repo = PgTaskInstanceRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+
+        let worker = repo
+            .register_worker(Uuid::new_v4(), "Stale Worker".to_string(), vec![task_kind.clone()], None)
+            .await
+            .unwrap();
+
+        let task = task_repo
+            .create_task(task_kind.id, None)
+            .await
+            .unwrap();
+        task_repo
+            .update_task_status(&task.id, common::TaskStatus::Running)
+            .await
+            .unwrap();
+        sqlx::query!(
+            "UPDATE tasks SET assigned_to = $1 WHERE id = $2",
+            worker.id,
+            task.id
+        )
+        .execute(&repo.core.writer)
+        .await
+        .unwrap();
+
+        let reaped = repo
+            .reap_stale_workers(Duration::from_secs(0))
+            .await
+            .unwrap();
+        assert!(reaped.contains(&worker.id));
+
+        let updated_worker = repo.get_worker_by_id(&worker.id).await.unwrap();
+        assert_eq!(updated_worker.state, WorkerState::Offline);
+
+        let updated_task = task_repo.get_task_by_id(&task.id, false).await.unwrap();
+        assert_eq!(updated_task.status, common::TaskStatus::Pending);
+        assert!(updated_task.assigned_to.is_none());
+    }
+
+    /// A task that has already exhausted its retries settles as `Failed`
+    /// when its worker is reaped, instead of bouncing back to `Pending`
+    /// forever.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn reap_stale_workers_fails_task_out_of_retries(pool: PgPool) {
+        use crate::repo::{PgTaskInstanceRepository, TaskInstanceRepository};
+
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgWorkerRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core.clone());
+        let task_repo = PgTaskInstanceRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+
+        let worker = repo
+            .register_worker(
+                Uuid::new_v4(),
+                "Stale Worker".to_string(),
+                vec![task_kind.clone()],
+                None,
+            )
+            .await
+            .unwrap();
+
+        let task = task_repo
+            .create_task(task_kind.id, None)
+            .await
+            .unwrap();
+        task_repo
+            .update_task_status(&task.id, common::TaskStatus::Running)
+            .await
+            .unwrap();
+        sqlx::query!(
+            "UPDATE tasks SET assigned_to = $1, retries = 999 WHERE id = $2",
+            worker.id,
+            task.id
+        )
+        .execute(&repo.core.writer)
+        .await
+        .unwrap();
+
+        repo.reap_stale_workers(Duration::from_secs(0))
+            .await
+            .unwrap();
+
+        let updated_task = task_repo.get_task_by_id(&task.id, false).await.unwrap();
+        assert_eq!(updated_task.status, common::TaskStatus::Failed);
+        assert!(updated_task.assigned_to.is_none());
+    }
+
+    /// With a zero threshold every worker that has never heartbeated shows
+    /// up in `find_stale_workers`, however recently it registered; with an
+    /// hour-long threshold neither does, and the read-only check flips no
+    /// one inactive.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn find_stale_workers_reports_without_reaping(pool: PgPool) {
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgWorkerRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+
+        let stale_worker = repo
+            .register_worker(Uuid::new_v4(), "Stale Worker".to_string(), vec![task_kind.clone()], None)
+            .await
+            .unwrap();
+        let fresh_worker = repo
+            .register_worker(Uuid::new_v4(), "Fresh Worker".to_string(), vec![task_kind], None)
+            .await
+            .unwrap();
+
+        let stale = repo
+            .find_stale_workers(Duration::from_secs(0))
+            .await
+            .unwrap();
+        assert!(stale.iter().any(|w| w.id == stale_worker.id));
+        assert!(stale.iter().any(|w| w.id == fresh_worker.id));
+
+        let not_yet_stale = repo
+            .find_stale_workers(Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert!(not_yet_stale.is_empty());
+
+        // Purely informational - neither worker was actually reaped.
+        let fetched = repo.get_worker_by_id(&stale_worker.id).await.unwrap();
+        assert_eq!(fetched.state, WorkerState::Active);
+    }
+
+    /// Exercises `set_worker_state`/`get_workers_by_state` through every
+    /// lifecycle transition: a freshly registered worker starts `Active`,
+    /// and each explicit transition both sticks on the worker itself and
+    /// is reflected in the `get_workers_by_state` listing for its new
+    /// state (and absent from the listing for its old one).
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn worker_state_transitions(pool: PgPool) {
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgWorkerRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+
+        let worker = repo
+            .register_worker(Uuid::new_v4(), "Transition Worker".to_string(), vec![task_kind], None)
+            .await
+            .unwrap();
+        assert_eq!(worker.state, WorkerState::Active);
+
+        for state in [WorkerState::Draining, WorkerState::Offline, WorkerState::Registered] {
+            repo.set_worker_state(&worker.id, state).await.unwrap();
+
+            let fetched = repo.get_worker_by_id(&worker.id).await.unwrap();
+            assert_eq!(fetched.state, state);
+
+            let listed = repo.get_workers_by_state(state).await.unwrap();
+            assert!(listed.iter().any(|w| w.id == worker.id));
+        }
+
+        let offline_workers = repo.get_workers_by_state(WorkerState::Offline).await.unwrap();
+        assert!(!offline_workers.iter().any(|w| w.id == worker.id));
+    }
+
+    /// Among two eligible, fresh-heartbeat workers declaring the same task
+    /// kind, `find_available_worker_for_kind` picks whichever currently has
+    /// fewer running tasks.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn find_available_worker_for_kind_prefers_least_loaded(pool: PgPool) {
+        use crate::repo::{PgTaskInstanceRepository, TaskInstanceRepository};
+
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgWorkerRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core.clone());
+        let task_repo = PgTaskInstanceRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+
+        let busy_worker = repo
+            .register_worker(Uuid::new_v4(), "Busy Worker".to_string(), vec![task_kind.clone()], Some(2))
+            .await
+            .unwrap();
+        let idle_worker = repo
+            .register_worker(Uuid::new_v4(), "Idle Worker".to_string(), vec![task_kind.clone()], Some(2))
+            .await
+            .unwrap();
+        repo.record_heartbeat(&busy_worker.id).await.unwrap();
+        repo.record_heartbeat(&idle_worker.id).await.unwrap();
+
+        let task = task_repo.create_task(task_kind.id, None).await.unwrap();
+        task_repo
+            .update_task_status(&task.id, common::TaskStatus::Running)
+            .await
+            .unwrap();
+        sqlx::query!(
+            "UPDATE tasks SET assigned_to = $1 WHERE id = $2",
+            busy_worker.id,
+            task.id
+        )
+        .execute(&repo.core.writer)
+        .await
+        .unwrap();
+
+        let available = repo
+            .find_available_worker_for_kind(task_kind.id, Duration::from_secs(60))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(available.id, idle_worker.id);
+    }
+
+    /// A worker that's stale (no recent heartbeat) or doesn't declare the
+    /// task kind at all isn't returned by `find_available_worker_for_kind`.
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn find_available_worker_for_kind_excludes_ineligible_workers(pool: PgPool) {
+        let core = PgRepositoryCore::new(pool.clone());
+        let repo = PgWorkerRepository::new(core.clone());
+        let task_kind_repo = PgTaskKindRepository::new(core);
+
+        let task_kind = task_kind_repo
+            .get_or_create_task_kind("Test task".to_string())
+            .await
+            .unwrap();
+        let other_kind = task_kind_repo
+            .get_or_create_task_kind("Other task".to_string())
+            .await
+            .unwrap();
+
+        // Never heartbeats - stale.
+        let _stale_worker = repo
+            .register_worker(Uuid::new_v4(), "Stale Worker".to_string(), vec![task_kind.clone()], Some(1))
+            .await
+            .unwrap();
+
+        // Doesn't declare `task_kind`.
+        let wrong_kind_worker = repo
+            .register_worker(Uuid::new_v4(), "Wrong Kind Worker".to_string(), vec![other_kind], Some(1))
+            .await
+            .unwrap();
+        repo.record_heartbeat(&wrong_kind_worker.id).await.unwrap();
+
+        let result = repo
+            .find_available_worker_for_kind(task_kind.id, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert!(result.is_none());
+    }
 }
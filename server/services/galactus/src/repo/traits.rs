@@ -1,12 +1,34 @@
-use std::time::SystemTime;
+use std::time::{Duration, SystemTime};
 
 use async_trait::async_trait;
 use common::{
-    models::{TaskInstance, TaskKind, TaskResult, Worker},
+    models::{TaskError, TaskInstance, TaskKind, TaskResult, Worker, WorkerState},
     TaskStatus,
 };
+use time::OffsetDateTime;
 use uuid::Uuid;
 
+/// When a scheduled task should next become eligible for assignment: a
+/// one-off time, or a recurring cron pattern.
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    Once(OffsetDateTime),
+    Cron(String),
+}
+
+/// Which terminal tasks `cleanup_finished_tasks` should delete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionMode {
+    /// Delete nothing.
+    KeepAll,
+    /// Delete every terminal task, regardless of outcome.
+    RemoveAll,
+    /// Delete only `Failed` tasks.
+    RemoveFailed,
+    /// Delete only `Completed` tasks.
+    RemoveDone,
+}
+
 /// Repository trait for managing task records in the database
 ///
 /// Provides methods for creating new tasks and retrieving existing tasks by their ID.
@@ -20,6 +42,42 @@ pub trait TaskInstanceRepository: Clone {
         input_data: Option<serde_json::Value>,
     ) -> Result<TaskInstance, sqlx::Error>;
 
+    /// Create a task that only becomes eligible for assignment once `schedule`
+    /// fires. A `Cron` schedule also keeps its pattern so that when the task
+    /// completes, a follow-up task for the next occurrence is enqueued.
+    async fn create_scheduled_task(
+        &self,
+        task_kind_id: Uuid,
+        input_data: Option<serde_json::Value>,
+        schedule: Schedule,
+    ) -> Result<TaskInstance, sqlx::Error>;
+
+    /// Atomically claims the oldest eligible pending task for a worker.
+    ///
+    /// Selects the oldest `Pending` task whose `scheduled_at` is due and
+    /// whose `task_kind_id` is one of `task_kinds`, using
+    /// `SELECT ... FOR UPDATE SKIP LOCKED` so concurrent workers never claim
+    /// the same row, then flips it to `Queued` with `assigned_to` set.
+    /// Returns `None` if no eligible task is available.
+    async fn fetch_next_task(
+        &self,
+        worker_id: &Uuid,
+        task_kinds: &[Uuid],
+    ) -> Result<Option<TaskInstance>, sqlx::Error>;
+
+    /// Alias for [`fetch_next_task`](Self::fetch_next_task) under the name
+    /// callers more often reach for when describing the operation as a claim
+    /// rather than a fetch. Defaults to the same atomic `FOR UPDATE SKIP
+    /// LOCKED` implementation; implementors only need to override this if
+    /// they want claiming to diverge from plain fetching.
+    async fn fetch_and_claim_task(
+        &self,
+        worker_id: &Uuid,
+        task_kinds: &[Uuid],
+    ) -> Result<Option<TaskInstance>, sqlx::Error> {
+        self.fetch_next_task(worker_id, task_kinds).await
+    }
+
     /// Get a task by its ID
     async fn get_task_by_id(
         &self,
@@ -34,7 +92,9 @@ pub trait TaskInstanceRepository: Clone {
         status: TaskStatus,
     ) -> Result<(), sqlx::Error>;
 
-    /// Upload an error result for a task, marking it as failed
+    /// Upload an error result for a task. If the task has retries left, it is
+    /// rescheduled with a backoff delay instead of being marked `Failed`; the
+    /// error is recorded in `task_results` on every attempt.
     async fn upload_task_error(
         &self,
         task_id: &Uuid,
@@ -49,6 +109,22 @@ pub trait TaskInstanceRepository: Clone {
         worker_id: &Uuid,
         output: serde_json::Value,
     ) -> Result<TaskResult, sqlx::Error>;
+
+    /// Deletes terminal tasks older than `older_than` according to `mode`,
+    /// cascading their `task_results`. Returns the number of tasks deleted.
+    async fn cleanup_finished_tasks(
+        &self,
+        mode: RetentionMode,
+        older_than: Duration,
+    ) -> Result<u64, sqlx::Error>;
+
+    /// Finds `Queued`/`Running` tasks whose assigned worker hasn't sent a
+    /// heartbeat within `timeout` and reclaims them: if the task still has
+    /// retries left it goes back to `Pending` with `assigned_to` cleared and
+    /// its retry counter bumped, otherwise it's marked `Failed` so a poison
+    /// task doesn't bounce between workers forever. Returns the ids of the
+    /// reclaimed tasks.
+    async fn reclaim_stale_tasks(&self, timeout: Duration) -> Result<Vec<Uuid>, sqlx::Error>;
 }
 
 /// Repository trait for managing task kind records in the database
@@ -72,12 +148,15 @@ pub trait TaskKindRepository: Clone {
 /// Provides methods for registering and managing workers that can process tasks.
 #[async_trait]
 pub trait WorkerRepository: Clone {
-    /// Register a new worker with its supported task types
+    /// Register a new worker with its supported task types. `max_concurrent_tasks`
+    /// caps how many tasks the dispatcher will hand this worker at once;
+    /// `None` falls back to [`common::models::DEFAULT_MAX_CONCURRENT_TASKS`].
     async fn register_worker(
         &self,
         id: Uuid,
         name: String,
         task_types: Vec<TaskKind>,
+        max_concurrent_tasks: Option<i32>,
     ) -> Result<Worker, sqlx::Error>;
 
     /// Get a worker by ID
@@ -86,12 +165,77 @@ pub trait WorkerRepository: Clone {
     /// Get all registered workers
     async fn get_all_workers(&self) -> Result<Vec<Worker>, sqlx::Error>;
 
-    /// Update a worker's active status
+    /// Update a worker's active status. Delegates to [`Self::set_worker_state`]
+    /// (`true` -> `Active`, `false` -> `Offline`) for backwards compatibility
+    /// with callers that only know about the old boolean flag.
     async fn set_worker_active(&self, worker_id: &Uuid, active: bool) -> Result<(), sqlx::Error>;
 
-    /// Record a heartbeat for a worker
+    /// Transitions a worker to `state`. Used by the scheduler to mark a
+    /// worker `Draining` (still finishing in-flight tasks, but no longer
+    /// eligible for new ones) ahead of a graceful shutdown, in addition to
+    /// the `Active`/`Offline` transitions `set_worker_active` covers.
+    async fn set_worker_state(&self, worker_id: &Uuid, state: WorkerState) -> Result<(), sqlx::Error>;
+
+    /// Returns every worker currently in `state`.
+    async fn get_workers_by_state(&self, state: WorkerState) -> Result<Vec<Worker>, sqlx::Error>;
+
+    /// Record a heartbeat for a worker. Upserted in place rather than
+    /// appended, so a worker's heartbeat history never grows past one row.
     async fn record_heartbeat(&self, worker_id: &Uuid) -> Result<(), sqlx::Error>;
 
     /// Get the latest heartbeat for a worker
     async fn get_latest_heartbeat(&self, worker_id: &Uuid) -> Result<SystemTime, sqlx::Error>;
+
+    /// Deletes heartbeat rows older than `cutoff`, returning how many were
+    /// removed, including rows of workers the reaper has already marked
+    /// offline.
+    async fn prune_heartbeats_older_than(&self, cutoff: Duration) -> Result<u64, sqlx::Error>;
+
+    /// Finds an `Active` worker that declares `task_kind_id`, has heartbeat
+    /// within `heartbeat_threshold`, and has fewer running tasks than its
+    /// `max_concurrent_tasks` - preferring whichever eligible worker is
+    /// currently least loaded. The chosen worker row is locked with `FOR
+    /// UPDATE SKIP LOCKED` only for the duration of this call: the
+    /// transaction commits before the worker is returned, so the lock does
+    /// not cover the caller's subsequent task claim, and two schedulers
+    /// calling this one after the other can both get the same worker.
+    /// Returns `None` if no worker qualifies.
+    async fn find_available_worker_for_kind(
+        &self,
+        task_kind_id: Uuid,
+        heartbeat_threshold: Duration,
+    ) -> Result<Option<Worker>, sqlx::Error>;
+
+    /// Issues a fresh opaque registration token for a worker, storing only
+    /// its hash, and returns the raw token. Callers must hand the raw token
+    /// to the worker and never persist or log it; it can't be recovered
+    /// once issued, only reissued (invalidating the previous one).
+    async fn issue_worker_token(&self, worker_id: &Uuid) -> Result<String, sqlx::Error>;
+
+    /// Checks whether `token` is the currently valid token for `worker_id`.
+    async fn verify_worker_token(
+        &self,
+        worker_id: &Uuid,
+        token: &str,
+    ) -> Result<bool, sqlx::Error>;
+}
+
+/// Repository trait for a task's persistent failure history.
+///
+/// Kept separate from `TaskResult` (which only holds a task's most recent
+/// outcome) so that every failed attempt across retries is preserved for
+/// auditing, even after the task eventually succeeds or is retried again.
+#[async_trait]
+pub trait TaskErrorRepository: Clone {
+    /// Records a single failed attempt for a task.
+    async fn record_error(
+        &self,
+        task_id: &Uuid,
+        worker_id: &Uuid,
+        message: &str,
+        retry_count: i32,
+    ) -> Result<TaskError, sqlx::Error>;
+
+    /// Returns every recorded failure for a task, oldest first.
+    async fn get_errors_for_task(&self, task_id: &Uuid) -> Result<Vec<TaskError>, sqlx::Error>;
 }
@@ -2,9 +2,12 @@
 pub mod test {
     use axum_test::TestServer;
     use sqlx::PgPool;
+    use tokio_util::sync::CancellationToken;
 
     use common::brokers::Broker;
+    use common::db::pools::DatabasePools;
 
+    use crate::repo::RetentionMode;
     use crate::setup_app;
 
     /// Initializes a test logger with debug level output that writes to the test writer.
@@ -19,7 +22,22 @@ pub mod test {
     /// Creates and returns a test server instance with the application router.
     /// This provides a way to make test HTTP requests against the API endpoints.
     pub async fn get_test_server(db_pools: PgPool, broker: Broker) -> TestServer {
-        let router = setup_app(db_pools, broker).await;
+        // Tests only have a single pool, so it doubles as both reader and
+        // writer - there's no replica to split reads onto.
+        let db_pools = DatabasePools {
+            reader: db_pools.clone(),
+            writer: db_pools,
+        };
+        let router = setup_app(
+            db_pools,
+            broker,
+            90,
+            30,
+            RetentionMode::RemoveDone,
+            60 * 60 * 24 * 7,
+            CancellationToken::new(),
+        )
+        .await;
         TestServer::new(router).unwrap()
     }
 }
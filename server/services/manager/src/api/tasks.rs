@@ -1,10 +1,18 @@
+use std::path::PathBuf;
+
 use axum::{
+    body::Body,
     extract::{Json, Path, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
     routing::{get, post, put},
     Router,
 };
-use serde::Deserialize;
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
 use tracing::{error, info};
 use utoipa::ToSchema;
 use uuid::Uuid;
@@ -12,16 +20,66 @@ use uuid::Uuid;
 use common::{models::TaskInstance, TaskStatus};
 
 use crate::{
-    repo::{TaskInstanceRepository, TaskKindRepository},
+    notifier::WebhookDeliveryStatus,
+    repo::{TaskInstanceRepository, TaskKindRepository, WorkerRepository},
     AppState,
 };
 
+/// Authorizes a worker-only request.
+///
+/// Tasks are published to a shared per-task_kind queue rather than assigned
+/// to a specific worker up front (see [`update_task_result`]), so there's no
+/// `assigned_to` to check the caller against the way galactus does. This
+/// crate also has no worker-token issuance yet, so the strongest credential
+/// available is confirming the `X-Worker-Id` header names a worker that's
+/// actually registered. Returns 400 if the header is missing or malformed,
+/// 401 if it doesn't name a registered worker.
+pub(crate) async fn authorize_worker(
+    headers: &HeaderMap,
+    state: &AppState,
+) -> Result<Uuid, (StatusCode, String)> {
+    let worker_id = headers
+        .get("X-Worker-Id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Uuid::parse_str(v).ok())
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            "Missing or invalid X-Worker-Id header".to_string(),
+        ))?;
+
+    state
+        .worker_repository
+        ._get_worker_by_id(&worker_id)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::RowNotFound => (
+                StatusCode::UNAUTHORIZED,
+                "Missing or invalid worker credentials".to_string(),
+            ),
+            _ => {
+                error!("Failed to verify worker: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to verify worker: {}", e),
+                )
+            }
+        })?;
+
+    Ok(worker_id)
+}
+
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/:id", get(get_task_by_id))
         .route("/", post(create_task))
         .route("/:id/status", put(update_task_status))
         .route("/:id/result", put(update_task_result))
+        .route("/:id/artifacts", get(list_artifacts))
+        .route(
+            "/:id/artifacts/:name",
+            put(upload_artifact).get(download_artifact),
+        )
+        .route("/:id/webhooks", get(list_webhook_deliveries))
 }
 
 /// Get a task by its UUID
@@ -76,6 +134,9 @@ async fn get_task_by_id(
 struct CreateTaskInput {
     task_kind_name: String,
     input_data: Option<serde_json::Value>,
+    /// Optional URL to POST a signed notification to on every state
+    /// transition this task goes through.
+    callback_url: Option<String>,
 }
 
 /// Create a new task
@@ -128,10 +189,25 @@ async fn create_task(
             )
         })?;
 
-    // Send the task to the worker queue
-    // We need to lock the broker because we're
-    // sharing it between threads
-    let worker_id = state
+    if let Some(callback_url) = task_input.callback_url {
+        state
+            .webhook_notifier
+            .register_callback(task.id, &callback_url)
+            .await
+            .map_err(|e| {
+                error!("Failed to register task callback: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to register task callback: {}", e),
+                )
+            })?;
+    }
+
+    // Publish the task to the queue for its task kind. We need to lock the
+    // broker because we're sharing it between threads. Any worker bound to
+    // that kind's queue can pick it up, so there's no single worker to
+    // assign the task to up front anymore.
+    state
         .broker
         .write()
         .await
@@ -145,19 +221,6 @@ async fn create_task(
             )
         })?;
 
-    // Assign the task the worker
-    state
-        .task_repository
-        .assign_task_to_worker(&task.id, &worker_id)
-        .await
-        .map_err(|e| {
-            error!("Failed to assign task to worker: {:?}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to assign task to worker: {}", e),
-            )
-        })?;
-
     Ok((StatusCode::CREATED, Json(task)))
 }
 
@@ -178,8 +241,10 @@ async fn create_task(
     request_body = String,
     responses(
         (status = 200, description = "Task status updated"),
-        (status = 400, description = "Invalid task status", content_type = "text/plain"),
+        (status = 400, description = "Invalid task status, or missing/invalid X-Worker-Id header", content_type = "text/plain"),
+        (status = 401, description = "Missing or invalid worker credentials", content_type = "text/plain"),
         (status = 404, description = "Task not found", content_type = "text/plain"),
+        (status = 409, description = "Illegal status transition", content_type = "text/plain"),
         (status = 500, description = "Internal server error", content_type = "text/plain")
     ),
     tag = "tasks"
@@ -187,6 +252,7 @@ async fn create_task(
 async fn update_task_status(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(task_status): Json<String>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     info!("Updating task status for task: {:?}", id);
@@ -211,9 +277,21 @@ async fn update_task_status(
             )
         })?;
 
+    authorize_worker(&headers, &state).await?;
+
+    if !task.status.can_transition_to(status.clone()) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "Cannot transition task from {} to {}",
+                task.status, status
+            ),
+        ));
+    }
+
     state
         .task_repository
-        .update_task_status(&task.id, status)
+        .update_task_status(&task.id, status.clone())
         .await
         .map_err(|e| {
             error!("Failed to update task status: {:?}", e);
@@ -223,6 +301,18 @@ async fn update_task_status(
             )
         })?;
 
+    state
+        .webhook_notifier
+        .notify(task.id, status, None, None)
+        .await
+        .map_err(|e| {
+            error!("Failed to enqueue status webhook: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to enqueue status webhook: {}", e),
+            )
+        })?;
+
     Ok(StatusCode::OK)
 }
 
@@ -235,6 +325,11 @@ struct TaskResultInput {
 
 /// Submit results or error for a task
 ///
+/// Since tasks are published to a shared per-task_kind queue rather than
+/// assigned to a specific worker up front, the task itself no longer knows
+/// which worker is reporting in - the caller identifies itself via the
+/// `X-Worker-Id` header so the result can be attributed to it.
+///
 /// # Arguments
 /// * `id` - UUID of the task to update
 /// * `result` - Task result data containing output or error
@@ -244,13 +339,15 @@ struct TaskResultInput {
 #[utoipa::path(
     put,
     description = "Submit results or error for a task. This should only be used by workers.",
-    path = "/tasks/:id/result", 
+    path = "/tasks/:id/result",
     params(
         ("id" = Uuid, Path, description = "Task ID to update")
     ),
     request_body = TaskResultInput,
     responses(
         (status = 200, description = "Task result updated"),
+        (status = 400, description = "Missing or invalid X-Worker-Id header", content_type = "text/plain"),
+        (status = 401, description = "Missing or invalid worker credentials", content_type = "text/plain"),
         (status = 404, description = "Task not found", content_type = "text/plain"),
         (status = 500, description = "Internal server error", content_type = "text/plain")
     ),
@@ -259,6 +356,7 @@ struct TaskResultInput {
 async fn update_task_result(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    headers: HeaderMap,
     Json(result): Json<TaskResultInput>,
 ) -> Result<StatusCode, (StatusCode, String)> {
     info!("Updating task result/error for task: {:?}", id);
@@ -276,18 +374,28 @@ async fn update_task_result(
             )
         })?;
 
+    // Tasks are no longer assigned to a specific worker up front (any worker
+    // bound to the task_kind's queue may have picked this one up), so the
+    // reporting worker identifies itself via the X-Worker-Id header instead
+    // of us reading `task.assigned_to`, and that identity is checked against
+    // the registered workers here.
+    let worker_id = authorize_worker(&headers, &state).await?;
+
+    let is_error = result.is_error;
+    let data_for_notify = result.data.clone();
+
     // If the task is in error state, upload the error, otherwise upload the result
     let result_upload = match result.is_error {
         true => {
             state
                 .task_repository
-                .upload_task_error(&task.id, &task.assigned_to.unwrap(), result.data)
+                .upload_task_error(&task.id, &worker_id, result.data)
                 .await
         }
         false => {
             state
                 .task_repository
-                .upload_task_result(&task.id, &task.assigned_to.unwrap(), result.data)
+                .upload_task_result(&task.id, &worker_id, result.data)
                 .await
         }
     };
@@ -301,9 +409,378 @@ async fn update_task_result(
         )
     })?;
 
+    // upload_task_error decides internally whether this task still has
+    // attempts left: it leaves the task Pending for a re-publish with
+    // backoff, or settles it Failed once attempts are exhausted. Either way
+    // that decision was just made in the database, so re-fetch it here
+    // rather than guessing at it from the request we were sent.
+    if is_error {
+        let retried_task = state
+            .task_repository
+            .get_task_by_id(&id, false)
+            .await
+            .map_err(|e| {
+                error!("Failed to re-fetch task after error upload: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to re-fetch task after error upload: {}", e),
+                )
+            })?;
+
+        state
+            .webhook_notifier
+            .notify(
+                retried_task.id,
+                retried_task.status.clone(),
+                None,
+                Some(data_for_notify),
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to enqueue error webhook: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to enqueue error webhook: {}", e),
+                )
+            })?;
+
+        match retried_task.status {
+            TaskStatus::Pending => {
+                state
+                    .broker
+                    .write()
+                    .await
+                    .publish(&retried_task)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to republish retried task to broker: {:?}", e);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Failed to republish retried task to broker: {}", e),
+                        )
+                    })?;
+            }
+            TaskStatus::Failed => {
+                state
+                    .task_repository
+                    .update_task_status(&retried_task.id, TaskStatus::DeadLetter)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to move exhausted task to dead_letter: {:?}", e);
+                        (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            format!("Failed to move exhausted task to dead_letter: {}", e),
+                        )
+                    })?;
+            }
+            _ => {}
+        }
+    } else {
+        state
+            .webhook_notifier
+            .notify(task.id, TaskStatus::Completed, Some(data_for_notify), None)
+            .await
+            .map_err(|e| {
+                error!("Failed to enqueue result webhook: {:?}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to enqueue result webhook: {}", e),
+                )
+            })?;
+    }
+
     Ok(StatusCode::OK)
 }
 
+/// List webhook delivery attempts recorded for a task, so callers can see
+/// whether their callback succeeded.
+///
+/// # Arguments
+/// * `id` - UUID of the task to list webhook deliveries for
+#[utoipa::path(
+    get,
+    description = "List webhook delivery attempts recorded for a task",
+    path = "/tasks/:id/webhooks",
+    params(
+        ("id" = Uuid, Path, description = "Task ID to list webhook deliveries for")
+    ),
+    responses(
+        (status = 200, description = "Webhook deliveries listed", body = [WebhookDeliveryStatus], content_type = "application/json"),
+        (status = 500, description = "Internal server error", content_type = "text/plain")
+    ),
+    tag = "tasks"
+)]
+async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<WebhookDeliveryStatus>>, (StatusCode, String)> {
+    state
+        .webhook_notifier
+        .delivery_status(id)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            error!("Failed to list webhook deliveries for task {:?}: {:?}", id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list webhook deliveries: {}", e),
+            )
+        })
+}
+
+/// Root directory artifacts are stored under, keyed by task id.
+fn artifacts_root() -> PathBuf {
+    PathBuf::from(
+        std::env::var("FASTTQ_ARTIFACTS_DIR").unwrap_or_else(|_| "./artifacts".to_string()),
+    )
+}
+
+/// Ensures `{artifacts_root}/{task_id}/` exists, tolerating a concurrent
+/// creation racing us to it.
+async fn reserve_artifacts_dir(task_id: Uuid) -> Result<PathBuf, std::io::Error> {
+    let dir = artifacts_root().join(task_id.to_string());
+    match tokio::fs::create_dir_all(&dir).await {
+        Ok(()) => Ok(dir),
+        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(dir),
+        Err(e) => Err(e),
+    }
+}
+
+/// Metadata describing a stored artifact. Kept as a `<name>.meta.json`
+/// sidecar next to the artifact's bytes, since this service's task
+/// repository has no column for it yet.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+struct ArtifactMetadata {
+    name: String,
+    size: u64,
+    content_type: String,
+    checksum: String,
+}
+
+fn artifact_meta_path(dir: &std::path::Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.meta.json", name))
+}
+
+async fn read_artifact_metadata(dir: &std::path::Path, name: &str) -> Option<ArtifactMetadata> {
+    let bytes = tokio::fs::read(artifact_meta_path(dir, name)).await.ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// Stream a worker's artifact bytes into the task's reserved artifacts
+/// directory, recording its size, content-type and checksum alongside it.
+///
+/// # Arguments
+/// * `id` - UUID of the task the artifact belongs to
+/// * `name` - Name of the artifact
+#[utoipa::path(
+    put,
+    description = "Stream a worker's artifact bytes to storage for a task. This should only be used by workers.",
+    path = "/tasks/:id/artifacts/:name",
+    params(
+        ("id" = Uuid, Path, description = "Task ID the artifact belongs to"),
+        ("name" = String, Path, description = "Artifact name")
+    ),
+    responses(
+        (status = 200, description = "Artifact stored", body = ArtifactMetadata, content_type = "application/json"),
+        (status = 500, description = "Internal server error", content_type = "text/plain")
+    ),
+    tag = "tasks"
+)]
+async fn upload_artifact(
+    Path((id, name)): Path<(Uuid, String)>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<ArtifactMetadata>, (StatusCode, String)> {
+    info!("Storing artifact {:?} for task: {:?}", name, id);
+
+    let dir = reserve_artifacts_dir(id).await.map_err(|e| {
+        error!("Failed to reserve artifacts dir for task {:?}: {:?}", id, e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to reserve artifacts directory: {}", e),
+        )
+    })?;
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    let mut file = tokio::fs::File::create(dir.join(&name)).await.map_err(|e| {
+        error!("Failed to create artifact file: {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to create artifact file: {}", e),
+        )
+    })?;
+
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| {
+            error!("Failed to read artifact upload stream: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to read artifact upload stream: {}", e),
+            )
+        })?;
+        hasher.update(&chunk);
+        size += chunk.len() as u64;
+        file.write_all(&chunk).await.map_err(|e| {
+            error!("Failed to write artifact bytes: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to write artifact bytes: {}", e),
+            )
+        })?;
+    }
+
+    let metadata = ArtifactMetadata {
+        name: name.clone(),
+        size,
+        content_type,
+        checksum: format!("{:x}", hasher.finalize()),
+    };
+
+    let meta_json = serde_json::to_vec(&metadata).map_err(|e| {
+        error!("Failed to serialize artifact metadata: {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to serialize artifact metadata: {}", e),
+        )
+    })?;
+    tokio::fs::write(artifact_meta_path(&dir, &name), meta_json)
+        .await
+        .map_err(|e| {
+            error!("Failed to write artifact metadata: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to write artifact metadata: {}", e),
+            )
+        })?;
+
+    Ok(Json(metadata))
+}
+
+/// Stream a previously stored artifact's bytes back.
+///
+/// # Arguments
+/// * `id` - UUID of the task the artifact belongs to
+/// * `name` - Name of the artifact
+#[utoipa::path(
+    get,
+    description = "Stream a stored artifact's bytes back for a task",
+    path = "/tasks/:id/artifacts/:name",
+    params(
+        ("id" = Uuid, Path, description = "Task ID the artifact belongs to"),
+        ("name" = String, Path, description = "Artifact name")
+    ),
+    responses(
+        (status = 200, description = "Artifact bytes", content_type = "application/octet-stream"),
+        (status = 404, description = "Artifact not found", content_type = "text/plain"),
+        (status = 500, description = "Internal server error", content_type = "text/plain")
+    ),
+    tag = "tasks"
+)]
+async fn download_artifact(
+    Path((id, name)): Path<(Uuid, String)>,
+) -> Result<Response, (StatusCode, String)> {
+    let dir = artifacts_root().join(id.to_string());
+    let content_type = read_artifact_metadata(&dir, &name)
+        .await
+        .map(|meta| meta.content_type)
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    let file = tokio::fs::File::open(dir.join(&name)).await.map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Artifact {} not found for task {}", name, id),
+            )
+        } else {
+            error!("Failed to open artifact: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to open artifact: {}", e),
+            )
+        }
+    })?;
+
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, content_type)
+        .body(Body::from_stream(ReaderStream::new(file)))
+        .map_err(|e| {
+            error!("Failed to build artifact response: {:?}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to build artifact response: {}", e),
+            )
+        })
+}
+
+/// List the artifacts stored for a task, so clients can enumerate results
+/// without guessing names.
+///
+/// # Arguments
+/// * `id` - UUID of the task to list artifacts for
+#[utoipa::path(
+    get,
+    description = "List the artifacts stored for a task",
+    path = "/tasks/:id/artifacts",
+    params(
+        ("id" = Uuid, Path, description = "Task ID to list artifacts for")
+    ),
+    responses(
+        (status = 200, description = "Artifacts listed", body = [ArtifactMetadata], content_type = "application/json"),
+        (status = 500, description = "Internal server error", content_type = "text/plain")
+    ),
+    tag = "tasks"
+)]
+async fn list_artifacts(
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<ArtifactMetadata>>, (StatusCode, String)> {
+    let dir = artifacts_root().join(id.to_string());
+
+    let mut entries = match tokio::fs::read_dir(&dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Json(Vec::new()));
+        }
+        Err(e) => {
+            error!("Failed to list artifacts for task {:?}: {:?}", id, e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list artifacts: {}", e),
+            ));
+        }
+    };
+
+    let mut artifacts = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(|e| {
+        error!("Failed to read artifacts directory entry: {:?}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read artifacts directory entry: {}", e),
+        )
+    })? {
+        let file_name = entry.file_name();
+        let Some(name) = file_name.to_str() else {
+            continue;
+        };
+        let Some(artifact_name) = name.strip_suffix(".meta.json") else {
+            continue;
+        };
+        if let Some(metadata) = read_artifact_metadata(&dir, artifact_name).await {
+            artifacts.push(metadata);
+        }
+    }
+
+    Ok(Json(artifacts))
+}
+
 #[cfg(test)]
 mod test {
     use axum::http::StatusCode;
@@ -312,6 +789,7 @@ mod test {
     use serde_json::json;
     use sqlx::PgPool;
     use tracing::info;
+    use uuid::Uuid;
 
     use crate::{
         repo::{
@@ -522,11 +1000,73 @@ mod test {
 
         let response = server
             .put(&format!("/tasks/{}/status", created_task.id))
+            .add_header("X-Worker-Id", test_worker.id.to_string())
             .json(&"running")
             .await;
         assert_eq!(response.status_code(), StatusCode::OK);
     }
 
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn update_task_status_fails_without_worker_id_header(db_pools: PgPool) {
+        let mut broker = get_mock_broker();
+        let test_worker = get_test_worker(&["test_task"]);
+        broker.register_worker(test_worker.clone()).await.unwrap();
+        let core = PgRepositoryCore::new(db_pools.clone());
+        let worker_repo = PgWorkerRepository::new(core);
+        worker_repo
+            .register_worker(test_worker.id, test_worker.name, test_worker.task_kind)
+            .await
+            .unwrap();
+
+        let server = get_test_server(db_pools, broker).await;
+
+        let create_response = server
+            .post("/tasks")
+            .json(&json!({
+                "task_kind_name": "test_task",
+                "input_data": null
+            }))
+            .await;
+        let created_task: TaskInstance = serde_json::from_str(&create_response.text()).unwrap();
+
+        let response = server
+            .put(&format!("/tasks/{}/status", created_task.id))
+            .json(&"running")
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn update_task_status_fails_with_unregistered_worker(db_pools: PgPool) {
+        let mut broker = get_mock_broker();
+        let test_worker = get_test_worker(&["test_task"]);
+        broker.register_worker(test_worker.clone()).await.unwrap();
+        let core = PgRepositoryCore::new(db_pools.clone());
+        let worker_repo = PgWorkerRepository::new(core);
+        worker_repo
+            .register_worker(test_worker.id, test_worker.name, test_worker.task_kind)
+            .await
+            .unwrap();
+
+        let server = get_test_server(db_pools, broker).await;
+
+        let create_response = server
+            .post("/tasks")
+            .json(&json!({
+                "task_kind_name": "test_task",
+                "input_data": null
+            }))
+            .await;
+        let created_task: TaskInstance = serde_json::from_str(&create_response.text()).unwrap();
+
+        let response = server
+            .put(&format!("/tasks/{}/status", created_task.id))
+            .add_header("X-Worker-Id", Uuid::new_v4().to_string())
+            .json(&"running")
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
     // Updating Task Result
 
     #[sqlx::test(migrator = "db_common::MIGRATOR")]
@@ -536,6 +1076,7 @@ mod test {
 
         let response = server
             .put("/tasks/123e4567-e89b-12d3-a456-426614174000/result")
+            .add_header("X-Worker-Id", Uuid::new_v4().to_string())
             .json(&json!({
                 "data": {"result": "test"},
                 "is_error": false
@@ -544,6 +1085,73 @@ mod test {
         assert_eq!(response.status_code(), StatusCode::NOT_FOUND);
     }
 
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn update_task_result_fails_without_worker_id_header(db_pools: PgPool) {
+        let mut broker = get_mock_broker();
+        let test_worker = get_test_worker(&["test_task"]);
+        broker.register_worker(test_worker.clone()).await.unwrap();
+        let core = PgRepositoryCore::new(db_pools.clone());
+        let worker_repo = PgWorkerRepository::new(core);
+        worker_repo
+            .register_worker(test_worker.id, test_worker.name, test_worker.task_kind)
+            .await
+            .unwrap();
+
+        let server = get_test_server(db_pools, broker).await;
+
+        let create_response = server
+            .post("/tasks")
+            .json(&json!({
+                "task_kind_name": "test_task",
+                "input_data": null
+            }))
+            .await;
+        let created_task: TaskInstance = serde_json::from_str(&create_response.text()).unwrap();
+
+        let response = server
+            .put(&format!("/tasks/{}/result", created_task.id))
+            .json(&json!({
+                "data": {"result": "test"},
+                "is_error": false
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[sqlx::test(migrator = "db_common::MIGRATOR")]
+    async fn update_task_result_fails_with_unregistered_worker(db_pools: PgPool) {
+        let mut broker = get_mock_broker();
+        let test_worker = get_test_worker(&["test_task"]);
+        broker.register_worker(test_worker.clone()).await.unwrap();
+        let core = PgRepositoryCore::new(db_pools.clone());
+        let worker_repo = PgWorkerRepository::new(core);
+        worker_repo
+            .register_worker(test_worker.id, test_worker.name, test_worker.task_kind)
+            .await
+            .unwrap();
+
+        let server = get_test_server(db_pools, broker).await;
+
+        let create_response = server
+            .post("/tasks")
+            .json(&json!({
+                "task_kind_name": "test_task",
+                "input_data": null
+            }))
+            .await;
+        let created_task: TaskInstance = serde_json::from_str(&create_response.text()).unwrap();
+
+        let response = server
+            .put(&format!("/tasks/{}/result", created_task.id))
+            .add_header("X-Worker-Id", Uuid::new_v4().to_string())
+            .json(&json!({
+                "data": {"result": "test"},
+                "is_error": false
+            }))
+            .await;
+        assert_eq!(response.status_code(), StatusCode::UNAUTHORIZED);
+    }
+
     #[sqlx::test(migrator = "db_common::MIGRATOR")]
     async fn update_task_result_successfully(db_pools: PgPool) {
         let mut broker = get_mock_broker();
@@ -569,6 +1177,7 @@ mod test {
 
         let response = server
             .put(&format!("/tasks/{}/result", created_task.id))
+            .add_header("X-Worker-Id", test_worker.id.to_string())
             .json(&json!({
                 "data": {"result": "test"},
                 "is_error": false
@@ -602,6 +1211,7 @@ mod test {
 
         let response = server
             .put(&format!("/tasks/{}/result", created_task.id))
+            .add_header("X-Worker-Id", test_worker.id.to_string())
             .json(&json!({
                 "data": {"error": "test error"},
                 "is_error": true
@@ -0,0 +1,272 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{error, warn};
+use uuid::Uuid;
+
+use common::TaskStatus;
+
+/// How often the delivery loop scans for due webhook deliveries.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Delivery attempts after which a webhook is given up on.
+const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+/// Base delay for the delivery backoff: `base * 2^attempts`.
+const BACKOFF_BASE_SECS: i64 = 2;
+
+/// Payload POSTed to a task's callback URL on every state transition.
+#[derive(Debug, Serialize)]
+struct WebhookPayload {
+    task_id: Uuid,
+    status: String,
+    result: Option<serde_json::Value>,
+    error: Option<serde_json::Value>,
+    timestamp: String,
+}
+
+/// The delivery status of a single webhook attempt, as surfaced to callers.
+#[derive(Debug, sqlx::FromRow, Serialize, utoipa::ToSchema)]
+pub struct WebhookDeliveryStatus {
+    pub url: String,
+    pub delivered: bool,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+}
+
+fn sign_payload(secret: &str, body: &str) -> String {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts a key of any length");
+    mac.update(body.as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+/// Durable outbound webhook delivery queue.
+///
+/// A task's callback URL is recorded in `task_callbacks` when it's created.
+/// Every later state transition enqueues a signed delivery into
+/// `webhook_deliveries`, which a background loop sends independently of the
+/// HTTP request that triggered the transition, retrying with exponential
+/// backoff until it succeeds or [`MAX_DELIVERY_ATTEMPTS`] is reached. Both
+/// tables are plain Postgres rows, so a pending delivery survives a restart.
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    pool: PgPool,
+    client: reqwest::Client,
+    signing_secret: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(pool: PgPool, signing_secret: String) -> Self {
+        Self {
+            pool,
+            client: reqwest::Client::new(),
+            signing_secret,
+        }
+    }
+
+    /// Ensures the notifier's tables exist.
+    pub async fn init(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS task_callbacks (
+                task_id UUID PRIMARY KEY,
+                url TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS webhook_deliveries (
+                id UUID PRIMARY KEY,
+                task_id UUID NOT NULL,
+                url TEXT NOT NULL,
+                payload JSONB NOT NULL,
+                signature TEXT NOT NULL,
+                attempts INT NOT NULL DEFAULT 0,
+                delivered BOOLEAN NOT NULL DEFAULT FALSE,
+                last_error TEXT,
+                next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records the callback URL a task should be notified at, so later
+    /// transitions know where to deliver to.
+    pub async fn register_callback(&self, task_id: Uuid, url: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO task_callbacks (task_id, url)
+            VALUES ($1, $2)
+            ON CONFLICT (task_id) DO UPDATE SET url = $2
+            "#,
+        )
+        .bind(task_id)
+        .bind(url)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Enqueues a webhook delivery for a task's state transition, if a
+    /// callback URL was registered for it. No-op otherwise.
+    pub async fn notify(
+        &self,
+        task_id: Uuid,
+        status: TaskStatus,
+        result: Option<serde_json::Value>,
+        error: Option<serde_json::Value>,
+    ) -> Result<(), sqlx::Error> {
+        let url: Option<(String,)> =
+            sqlx::query_as("SELECT url FROM task_callbacks WHERE task_id = $1")
+                .bind(task_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        let Some((url,)) = url else {
+            return Ok(());
+        };
+
+        let payload = WebhookPayload {
+            task_id,
+            status: status.into(),
+            result,
+            error,
+            timestamp: OffsetDateTime::now_utc().to_string(),
+        };
+        let body = serde_json::to_string(&payload).unwrap_or_else(|_| "{}".to_string());
+        let signature = sign_payload(&self.signing_secret, &body);
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (id, task_id, url, payload, signature)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(task_id)
+        .bind(url)
+        .bind(serde_json::from_str::<serde_json::Value>(&body).unwrap_or(serde_json::Value::Null))
+        .bind(signature)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Delivery status of every webhook attempt recorded for a task.
+    pub async fn delivery_status(
+        &self,
+        task_id: Uuid,
+    ) -> Result<Vec<WebhookDeliveryStatus>, sqlx::Error> {
+        sqlx::query_as(
+            r#"
+            SELECT url, delivered, attempts, last_error
+            FROM webhook_deliveries
+            WHERE task_id = $1
+            ORDER BY created_at
+            "#,
+        )
+        .bind(task_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Spawns the background loop that sends due, undelivered webhooks and
+    /// retries failures with backoff.
+    pub fn spawn_delivery_loop(self) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(POLL_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(err) = self.deliver_due().await {
+                    error!(%err, "Webhook delivery sweep failed");
+                }
+            }
+        });
+    }
+
+    async fn deliver_due(&self) -> Result<(), sqlx::Error> {
+        let due: Vec<(Uuid, String, serde_json::Value, String, i32)> = sqlx::query_as(
+            r#"
+            SELECT id, url, payload, signature, attempts
+            FROM webhook_deliveries
+            WHERE NOT delivered AND next_attempt_at <= NOW() AND attempts < $1
+            ORDER BY created_at
+            LIMIT 20
+            "#,
+        )
+        .bind(MAX_DELIVERY_ATTEMPTS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for (id, url, payload, signature, attempts) in due {
+            let outcome = self
+                .client
+                .post(&url)
+                .header("X-FastTQ-Signature", signature)
+                .json(&payload)
+                .send()
+                .await;
+
+            match outcome {
+                Ok(resp) if resp.status().is_success() => {
+                    sqlx::query("UPDATE webhook_deliveries SET delivered = TRUE WHERE id = $1")
+                        .bind(id)
+                        .execute(&self.pool)
+                        .await?;
+                }
+                Ok(resp) => {
+                    self.record_failed_attempt(id, attempts, format!("HTTP {}", resp.status()))
+                        .await?;
+                }
+                Err(err) => {
+                    warn!(%id, %err, "Webhook delivery attempt failed");
+                    self.record_failed_attempt(id, attempts, err.to_string())
+                        .await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn record_failed_attempt(
+        &self,
+        id: Uuid,
+        attempts: i32,
+        err: String,
+    ) -> Result<(), sqlx::Error> {
+        let next_attempts = attempts + 1;
+        let delay_secs = BACKOFF_BASE_SECS * 2i64.saturating_pow(next_attempts.max(0) as u32);
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET attempts = $2, last_error = $3, next_attempt_at = NOW() + ($4 * INTERVAL '1 second')
+            WHERE id = $1
+            "#,
+        )
+        .bind(id)
+        .bind(next_attempts)
+        .bind(err)
+        .bind(delay_secs)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}